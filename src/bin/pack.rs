@@ -0,0 +1,90 @@
+//! Packs asset files into an archive the vfs can mount.
+//!
+//! ```text
+//! pack [--no-compress] <output> <root> [<dir>...]
+//! ```
+//!
+//! Files are stored under their path relative to `<root>`. If no `<dir>`s
+//! are given the whole of `<root>` is packed. For this repo that is
+//! `cargo run --bin pack -- target/release/assets.pak . src`.
+
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use wgpu_template::resources::pack::{Compression, PackWriter};
+
+const USAGE: &str = "Usage: pack [--no-compress] <output> <root> [<dir>...]";
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+
+    let mut compression = Compression::Lz4;
+    let mut positional = Vec::new();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--no-compress" => compression = Compression::None,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    let (Some(output), Some(root)) = (positional.next(), positional.next()) else {
+        anyhow::bail!("{USAGE}");
+    };
+    let root = PathBuf::from(root);
+    let mut dirs: Vec<PathBuf> = positional.map(|dir| root.join(dir)).collect();
+    if dirs.is_empty() {
+        dirs.push(root.clone());
+    }
+
+    let mut files = Vec::new();
+    for dir in &dirs {
+        collect_files(dir, &mut files)?;
+    }
+    files.sort();
+
+    let mut writer = PackWriter::new();
+    for path in &files {
+        let name = path
+            .strip_prefix(&root)?
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let data = std::fs::read(path)?;
+        log::info!("{name} ({} bytes)", data.len());
+        writer.add(&name, &data, compression);
+    }
+
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&output)?);
+    writer.write(&mut file)?;
+    file.flush()?;
+
+    println!("Packed {} files into {output}", files.len());
+
+    Ok(())
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if hidden {
+            continue;
+        }
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,553 @@
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+
+use crate::{
+    input::{gamepad::DeadZones, Bindings},
+    resources::{
+        camera::{fly::FlySettings, orbit::OrbitSettings},
+        fs::load_text,
+    },
+};
+
+pub const CONFIG_VERSION: u32 = 2;
+
+/// `MIGRATIONS[n]` upgrades a config from version `n` to `n + 1`.
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[
+    // Version 0 is the unversioned format. Version 1 only adds `version`.
+    |_| {},
+    // Version 2 stores the window size in logical pixels. The scale factor
+    // of the old physical size is only known once there is a window.
+    // A missing side had the version 1 default.
+    |fields| {
+        let (width, height) = (fields.remove("width"), fields.remove("height"));
+        if width.is_some() || height.is_some() {
+            let size = vec![
+                width.unwrap_or_else(|| 1920.into()),
+                height.unwrap_or_else(|| 1080.into()),
+            ];
+            fields.insert("physical_size".to_owned(), Value::Array(size));
+        }
+    },
+];
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Config {
+    pub(crate) version: u32,
+    /// Inner size of the window in logical pixels.
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    /// Inner size in physical pixels, from configs before version 2. The
+    /// window replaces it with `width` and `height` once it knows its scale
+    /// factor.
+    pub(crate) physical_size: Option<(u32, u32)>,
+    pub(crate) scroll_sensitivity: f32,
+    pub(crate) touch_sensitivity: f32,
+    pub(crate) fullscreen: bool,
+    /// Use exclusive fullscreen with `video_mode` instead of borderless.
+    pub(crate) exclusive_fullscreen: bool,
+    /// Defaults to the native resolution of the monitor.
+    pub(crate) video_mode: Option<VideoMode>,
+    pub(crate) monitor: Option<String>,
+    /// Outer position of the window in physical pixels.
+    pub(crate) position: Option<(i32, i32)>,
+    pub(crate) maximized: bool,
+    pub(crate) bindings: Bindings,
+    pub(crate) dead_zones: DeadZones,
+    pub(crate) fly_camera: FlySettings,
+    pub(crate) orbit_camera: OrbitSettings,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            width: 1920,
+            height: 1080,
+            physical_size: None,
+            scroll_sensitivity: 0.5,
+            touch_sensitivity: 0.1,
+            monitor: None,
+            fullscreen: false,
+            exclusive_fullscreen: false,
+            video_mode: None,
+            position: None,
+            maximized: false,
+            bindings: Bindings::default(),
+            dead_zones: DeadZones::default(),
+            fly_camera: FlySettings::default(),
+            orbit_camera: OrbitSettings::default(),
+        }
+    }
+}
+
+/// A video mode for exclusive fullscreen. Unset refresh rate and bit depth
+/// pick the highest available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct VideoMode {
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub refresh_rate_millihertz: Option<u32>,
+    #[serde(default)]
+    pub bit_depth: Option<u16>,
+}
+
+/// A field from a config file that couldn't be used and was replaced by its
+/// default.
+#[derive(Debug, Clone)]
+pub struct Rejected {
+    pub field: String,
+    pub reason: String,
+}
+
+impl Config {
+    /// Loads the config at `path`. Fails only if the file can't be read or
+    /// isn't valid JSON. Individual fields that are invalid are logged and
+    /// replaced with their defaults.
+    pub async fn load(path: &str) -> anyhow::Result<Self> {
+        let text = load_text(path).await?;
+        let value = serde_json::from_str(&text)?;
+        let (config, rejected) = Self::from_value(value);
+        for Rejected { field, reason } in rejected {
+            log::warn!("{path}: ignoring `{field}`: {reason}");
+        }
+        Ok(config)
+    }
+
+    /// Migrates `value` to the current version and deserializes it one field
+    /// at a time, so a bad field doesn't take the rest of the config with it.
+    ///
+    /// A config from a newer version is loaded as far as it is understood
+    /// but keeps its version, see [`Config::is_newer`].
+    pub fn from_value(value: Value) -> (Self, Vec<Rejected>) {
+        let mut rejected = Vec::new();
+
+        let Value::Object(mut fields) = value else {
+            rejected.push(Rejected {
+                field: String::new(),
+                reason: "expected an object".to_owned(),
+            });
+            return (Self::default(), rejected);
+        };
+
+        let version = fields.get("version").and_then(Value::as_u64).unwrap_or(0);
+        if version > CONFIG_VERSION as u64 {
+            log::warn!("Config version {version} is newer than {CONFIG_VERSION}");
+        } else {
+            for migration in MIGRATIONS.iter().skip(version as usize) {
+                migration(&mut fields);
+            }
+            fields.insert("version".to_owned(), CONFIG_VERSION.into());
+        }
+
+        let Ok(Value::Object(defaults)) = serde_json::to_value(Self::default()) else {
+            unreachable!("Config serializes to an object");
+        };
+
+        let mut merged = defaults.clone();
+        for (field, value) in fields {
+            if !defaults.contains_key(&field) {
+                rejected.push(Rejected {
+                    field,
+                    reason: "unknown field".to_owned(),
+                });
+                continue;
+            }
+
+            let mut candidate = defaults.clone();
+            candidate.insert(field.clone(), value.clone());
+            let result = serde_json::from_value::<Self>(Value::Object(candidate))
+                .map_err(|e| e.to_string())
+                .and_then(|config| config.validate_field(&field));
+
+            match result {
+                Ok(()) => {
+                    merged.insert(field, value);
+                }
+                Err(reason) => rejected.push(Rejected { field, reason }),
+            }
+        }
+
+        let config = serde_json::from_value(Value::Object(merged)).unwrap_or_default();
+
+        (config, rejected)
+    }
+
+    /// Whether this was loaded from a newer version. Saving it would drop
+    /// the fields this version doesn't know about, so it shouldn't be.
+    pub fn is_newer(&self) -> bool {
+        self.version > CONFIG_VERSION
+    }
+
+    fn validate_field(&self, field: &str) -> Result<(), String> {
+        match field {
+            "width" => non_zero(self.width),
+            "height" => non_zero(self.height),
+            "physical_size" => self
+                .physical_size
+                .map_or(Ok(()), |(width, height)| non_zero(width.min(height))),
+            "scroll_sensitivity" => sensitivity(self.scroll_sensitivity),
+            "touch_sensitivity" => sensitivity(self.touch_sensitivity),
+            "dead_zones" => dead_zone(self.dead_zones.stick)
+                .and_then(|()| dead_zone(self.dead_zones.trigger)),
+            "fly_camera" => fly_settings(&self.fly_camera),
+            "orbit_camera" => orbit_settings(&self.orbit_camera),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn non_zero(value: u32) -> Result<(), String> {
+    if value == 0 {
+        return Err("must not be zero".to_owned());
+    }
+    Ok(())
+}
+
+fn sensitivity(value: f32) -> Result<(), String> {
+    if !(value > 0.0 && value <= 100.0) {
+        return Err(format!("{value} is outside of (0, 100]"));
+    }
+    Ok(())
+}
+
+fn dead_zone(value: f32) -> Result<(), String> {
+    if !(0.0..1.0).contains(&value) {
+        return Err(format!("{value} is outside of [0, 1)"));
+    }
+    Ok(())
+}
+
+fn fly_settings(settings: &FlySettings) -> Result<(), String> {
+    let FlySettings {
+        speed,
+        fast_multiplier,
+        acceleration,
+        damping,
+    } = *settings;
+    for (name, value) in [
+        ("speed", speed),
+        ("fast_multiplier", fast_multiplier),
+        ("acceleration", acceleration),
+    ] {
+        if !(value > 0.0 && value.is_finite()) {
+            return Err(format!("{name} must be positive"));
+        }
+    }
+    if !(damping >= 0.0 && damping.is_finite()) {
+        return Err("damping must not be negative".to_owned());
+    }
+    Ok(())
+}
+
+fn orbit_settings(settings: &OrbitSettings) -> Result<(), String> {
+    if !(settings.min_distance > 0.0 && settings.min_distance <= settings.max_distance) {
+        return Err("distances must be positive with min_distance <= max_distance".to_owned());
+    }
+    let max_pitch = std::f32::consts::FRAC_PI_2;
+    if !(-max_pitch < settings.min_pitch
+        && settings.min_pitch <= settings.max_pitch
+        && settings.max_pitch < max_pitch)
+    {
+        return Err("pitch limits must be within (-PI/2, PI/2) with min_pitch <= max_pitch".to_owned());
+    }
+    if !(settings.damping >= 0.0 && settings.damping.is_finite()) {
+        return Err("damping must not be negative".to_owned());
+    }
+    Ok(())
+}
+
+/// Environment variables named `WGPU_TEMPLATE_<FIELD>` override config
+/// fields, e.g. `WGPU_TEMPLATE_WIDTH=1280`.
+pub const ENV_PREFIX: &str = "WGPU_TEMPLATE_";
+
+/// Config fields set from outside the config file. Later overrides win over
+/// earlier ones, so env vars are added before command line arguments.
+///
+/// Values are parsed as JSON, falling back to a plain string, so
+/// `--width 1280`, `--fullscreen true` and `--monitor DP-1` all work.
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    fields: Vec<(String, String)>,
+}
+
+impl Overrides {
+    pub fn from_env() -> Self {
+        Self::from_vars(std::env::vars())
+    }
+
+    /// Picks the [`ENV_PREFIX`]ed variables out of `vars`, in field order.
+    pub fn from_vars(vars: impl Iterator<Item = (String, String)>) -> Self {
+        let mut vars: HashMap<_, _> = vars.collect();
+        let mut overrides = Self::default();
+        for field in field_names() {
+            let var = format!("{ENV_PREFIX}{}", field.to_uppercase());
+            if let Some(value) = vars.remove(&var) {
+                overrides.fields.push((field, value));
+            }
+        }
+        overrides
+    }
+
+    /// `field` may be written in kebab case, as on the command line. Fails
+    /// if [`Config`] has no such field.
+    pub fn set(&mut self, field: &str, value: &str) -> anyhow::Result<&mut Self> {
+        let field = field.replace('-', "_");
+        if !field_names().contains(&field) {
+            anyhow::bail!("Unknown config field {field:?}");
+        }
+        self.fields.push((field, value.to_owned()));
+        Ok(self)
+    }
+
+    pub fn extend(&mut self, other: Overrides) -> &mut Self {
+        self.fields.extend(other.fields);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Fails if any override has a value its field doesn't accept. Fields
+    /// are validated on their own, so this doesn't depend on the config
+    /// they are applied to.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let (_, rejected) = self.apply_quiet(&Config::default());
+        let errors: Vec<_> = rejected
+            .into_iter()
+            .map(|Rejected { field, reason }| format!("`{field}`: {reason}"))
+            .collect();
+        if !errors.is_empty() {
+            anyhow::bail!("Invalid config overrides: {}", errors.join(", "));
+        }
+        Ok(())
+    }
+
+    /// Returns `config` with the overrides applied. Overrides that fail to
+    /// validate are logged and ignored.
+    pub fn apply(&self, config: &Config) -> Config {
+        let (config, rejected) = self.apply_quiet(config);
+        for Rejected { field, reason } in rejected {
+            log::warn!("Ignoring override for `{field}`: {reason}");
+        }
+        config
+    }
+
+    /// Resets fields that still hold their overridden value to what `file`
+    /// has, so saving `config` doesn't persist the overrides. Fields that
+    /// changed at runtime are kept.
+    pub fn revert(&self, config: &Config, file: &Config) -> Config {
+        let (overridden, _) = self.apply_quiet(file);
+        let (Ok(Value::Object(mut fields)), Ok(Value::Object(overridden)), Ok(Value::Object(file))) = (
+            serde_json::to_value(config),
+            serde_json::to_value(overridden),
+            serde_json::to_value(file),
+        ) else {
+            return config.clone();
+        };
+
+        for (field, _) in &self.fields {
+            if fields.get(field) == overridden.get(field) {
+                if let Some(value) = file.get(field) {
+                    fields.insert(field.clone(), value.clone());
+                }
+            }
+        }
+
+        serde_json::from_value(Value::Object(fields)).unwrap_or_else(|_| config.clone())
+    }
+
+    fn apply_quiet(&self, config: &Config) -> (Config, Vec<Rejected>) {
+        let Ok(Value::Object(mut fields)) = serde_json::to_value(config) else {
+            return (config.clone(), Vec::new());
+        };
+
+        for (field, value) in &self.fields {
+            let value = serde_json::from_str(value).unwrap_or_else(|_| Value::from(value.as_str()));
+            fields.insert(field.clone(), value);
+        }
+
+        Config::from_value(Value::Object(fields))
+    }
+}
+
+fn field_names() -> Vec<String> {
+    match serde_json::to_value(Config::default()) {
+        Ok(Value::Object(fields)) => fields
+            .into_iter()
+            .map(|(field, _)| field)
+            .filter(|field| field != "version" && field != "physical_size")
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn rejected_fields(rejected: &[Rejected]) -> Vec<&str> {
+        rejected.iter().map(|r| r.field.as_str()).collect()
+    }
+
+    #[test]
+    fn migrates_unversioned_configs() {
+        let (config, rejected) = Config::from_value(json!({
+            "fullscreen": true,
+            "monitor": "DP-1",
+        }));
+        assert!(rejected.is_empty());
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert!(config.fullscreen);
+        assert_eq!(config.monitor.as_deref(), Some("DP-1"));
+        assert!(!config.is_newer());
+    }
+
+    #[test]
+    fn migrates_physical_sizes() {
+        let (config, rejected) = Config::from_value(json!({
+            "version": 1,
+            "width": 3840,
+            "height": 2160,
+        }));
+        assert!(rejected.is_empty());
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.physical_size, Some((3840, 2160)));
+        assert_eq!(config.width, Config::default().width);
+
+        // Saved and loaded again before the window converted it.
+        let (reloaded, rejected) = Config::from_value(serde_json::to_value(&config).unwrap());
+        assert!(rejected.is_empty());
+        assert_eq!(reloaded.physical_size, Some((3840, 2160)));
+
+        for (fields, size) in [
+            (json!({ "version": 1, "width": 3840 }), (3840, 1080)),
+            (json!({ "height": 2160 }), (1920, 2160)),
+        ] {
+            let (config, rejected) = Config::from_value(fields);
+            assert!(rejected.is_empty());
+            assert_eq!(config.physical_size, Some(size));
+        }
+        let (config, _) = Config::from_value(json!({ "version": 1 }));
+        assert_eq!(config.physical_size, None);
+
+        let (config, _) = Config::from_value(json!({
+            "version": CONFIG_VERSION,
+            "width": 1280,
+            "height": 720,
+        }));
+        assert_eq!(config.physical_size, None);
+        assert_eq!((config.width, config.height), (1280, 720));
+    }
+
+    #[test]
+    fn rejects_fields_one_at_a_time() {
+        let (config, rejected) = Config::from_value(json!({
+            "version": CONFIG_VERSION,
+            "width": 0,
+            "height": 600,
+            "scroll_sensitivity": "fast",
+            "touch_sensitivity": 0.5,
+            "dead_zones": { "stick": 1.5, "trigger": 0.1 },
+        }));
+        assert_eq!(
+            rejected_fields(&rejected),
+            ["dead_zones", "scroll_sensitivity", "width"]
+        );
+        let defaults = Config::default();
+        assert_eq!(config.width, defaults.width);
+        assert_eq!(config.height, 600);
+        assert_eq!(config.scroll_sensitivity, defaults.scroll_sensitivity);
+        assert_eq!(config.touch_sensitivity, 0.5);
+        assert_eq!(config.dead_zones.stick, defaults.dead_zones.stick);
+    }
+
+    #[test]
+    fn rejects_unknown_fields_and_non_objects() {
+        let (config, rejected) = Config::from_value(json!({
+            "version": CONFIG_VERSION,
+            "colour": "blue",
+            "maximized": true,
+        }));
+        assert_eq!(rejected_fields(&rejected), ["colour"]);
+        assert!(config.maximized);
+
+        let (_, rejected) = Config::from_value(json!([1, 2]));
+        assert_eq!(rejected_fields(&rejected), [""]);
+    }
+
+    #[test]
+    fn keeps_the_version_of_newer_configs() {
+        let newer = CONFIG_VERSION + 1;
+        let (config, rejected) = Config::from_value(json!({
+            "version": newer,
+            "maximized": true,
+            "added_later": 1,
+        }));
+        assert_eq!(rejected_fields(&rejected), ["added_later"]);
+        assert!(config.maximized);
+        assert_eq!(config.version, newer);
+        assert!(config.is_newer());
+
+        // Overrides go through from_value again and must not lose it.
+        let mut overrides = Overrides::default();
+        overrides.set("fullscreen", "true").unwrap();
+        assert!(overrides.apply(&config).is_newer());
+    }
+
+    #[test]
+    fn overrides_follow_precedence() {
+        let vars = [
+            ("WGPU_TEMPLATE_TOUCH_SENSITIVITY", "0.25"),
+            ("WGPU_TEMPLATE_NOT_A_FIELD", "1"),
+            ("TOUCH_SENSITIVITY", "2"),
+        ];
+        let mut overrides =
+            Overrides::from_vars(vars.map(|(k, v)| (k.to_owned(), v.to_owned())).into_iter());
+        overrides.validate().unwrap();
+        assert_eq!(overrides.apply(&Config::default()).touch_sensitivity, 0.25);
+
+        let mut cli = Overrides::default();
+        cli.set("touch-sensitivity", "0.75").unwrap();
+        overrides.extend(cli);
+        overrides.validate().unwrap();
+        let file = Config {
+            touch_sensitivity: 0.5,
+            ..Default::default()
+        };
+        let config = overrides.apply(&file);
+        assert_eq!(config.touch_sensitivity, 0.75);
+
+        // Not written back, unless it changed at runtime.
+        assert_eq!(overrides.revert(&config, &file).touch_sensitivity, 0.5);
+        let changed = Config {
+            touch_sensitivity: 1.0,
+            ..config
+        };
+        assert_eq!(overrides.revert(&changed, &file).touch_sensitivity, 1.0);
+    }
+
+    #[test]
+    fn rejects_bad_overrides() {
+        let mut overrides = Overrides::default();
+        assert!(overrides.set("colour", "blue").is_err());
+        assert!(overrides.set("version", "2").is_err());
+        assert!(overrides.set("physical_size", "[800, 600]").is_err());
+        assert!(overrides.is_empty());
+
+        overrides.set("width", "0").unwrap();
+        overrides.set("fullscreen", "sometimes").unwrap();
+        let error = overrides.validate().unwrap_err().to_string();
+        assert!(error.contains("width") && error.contains("fullscreen"));
+
+        // Applying skips them.
+        let config = overrides.apply(&Config::default());
+        assert_eq!(config.width, Config::default().width);
+        assert!(!config.fullscreen);
+    }
+}
@@ -1,12 +1,22 @@
 use anyhow::Context as _;
 
-use crate::{Config, window::Window};
+use crate::window::Window;
 
 pub struct Context<'a> {
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
-    surface: wgpu::Surface<'a>,
-    surf_config: wgpu::SurfaceConfiguration,
+    target: Target<'a>,
+}
+
+/// What frames are rendered to.
+enum Target<'a> {
+    Surface {
+        surface: wgpu::Surface<'a>,
+        config: wgpu::SurfaceConfiguration,
+    },
+    /// Used when running headless, frames can be read back with
+    /// [`Context::read_pixels`].
+    Texture(wgpu::Texture),
 }
 
 impl<'a> Context<'a> {
@@ -15,27 +25,7 @@ impl<'a> Context<'a> {
 
         let surface = instance.create_surface(window.as_ref())?;
 
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                compatible_surface: Some(&surface),
-                power_preference: wgpu::PowerPreference::HighPerformance,
-                ..Default::default()
-            })
-            .await
-            .context("No valid adapter")?;
-
-        println!("info: {:?}", adapter.get_info());
-
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    label: None,
-                    required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits::downlevel_defaults(),
-                },
-                None,
-            )
-            .await?;
+        let (adapter, device, queue) = request_device(&instance, Some(&surface)).await?;
 
         let caps = surface.get_capabilities(&adapter);
         let format = caps.formats[0];
@@ -54,44 +44,176 @@ impl<'a> Context<'a> {
         Ok(Self {
             device,
             queue,
-            surface,
-            surf_config,
+            target: Target::Surface {
+                surface,
+                config: surf_config,
+            },
         })
     }
 
-    pub fn resize(&mut self, width: u32, height: u32) {
-        self.surf_config.width = width.max(1);
-        self.surf_config.height = height.max(1);
-        self.surface.configure(&self.device, &self.surf_config);
+    /// Renders into a texture instead of a window.
+    pub async fn headless(width: u32, height: u32) -> anyhow::Result<Context<'static>> {
+        let instance = wgpu::Instance::new(Default::default());
+        let (_, device, queue) = request_device(&instance, None).await?;
+        let texture = create_target_texture(&device, width, height);
+
+        Ok(Context {
+            device,
+            queue,
+            target: Target::Texture(texture),
+        })
     }
 
-    pub fn render(&mut self, f: impl FnOnce(&mut Frame, &Self)) {
-        let target = match self.surface.get_current_texture() {
-            Ok(target) => target,
-            Err(wgpu::SurfaceError::Outdated) => {
-                self.surface.configure(&self.device, &self.surf_config);
-                return;
+    pub fn resize(&mut self, width: u32, height: u32) {
+        match &mut self.target {
+            Target::Surface { surface, config } => {
+                config.width = width.max(1);
+                config.height = height.max(1);
+                surface.configure(&self.device, config);
             }
-            Err(e) => {
-                panic!("{}", e);
+            Target::Texture(texture) => {
+                *texture = create_target_texture(&self.device, width, height);
             }
+        }
+    }
+
+    pub fn render(&mut self, f: impl FnOnce(&mut Frame, &Self)) {
+        let (surface_texture, view) = match &self.target {
+            Target::Surface { surface, config } => match surface.get_current_texture() {
+                Ok(target) => {
+                    let view = target.texture.create_view(&Default::default());
+                    (Some(target), view)
+                }
+                Err(wgpu::SurfaceError::Outdated) => {
+                    surface.configure(&self.device, config);
+                    return;
+                }
+                Err(e) => {
+                    panic!("{}", e);
+                }
+            },
+            Target::Texture(texture) => (None, texture.create_view(&Default::default())),
         };
 
         let encoder = self.device.create_command_encoder(&Default::default());
-        let mut frame = Frame { encoder, target };
+        let mut frame = Frame { encoder, view };
 
         f(&mut frame, self);
 
         self.queue.submit([frame.encoder.finish()]);
-        frame.target.present();
+        if let Some(surface_texture) = surface_texture {
+            surface_texture.present();
+        }
     }
 
     pub fn surface_format(&self) -> wgpu::TextureFormat {
-        self.surf_config.format
+        match &self.target {
+            Target::Surface { config, .. } => config.format,
+            Target::Texture(texture) => texture.format(),
+        }
+    }
+
+    /// Tightly packed pixels of the last frame. Only works headless.
+    pub async fn read_pixels(&self) -> anyhow::Result<Vec<u8>> {
+        let Target::Texture(texture) = &self.target else {
+            anyhow::bail!("Only headless frames can be read back");
+        };
+
+        let (width, height) = (texture.width(), texture.height());
+        let block_size = texture.format().block_copy_size(None).unwrap_or(4);
+        let row_size = width * block_size;
+        let padded_row_size = row_size.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("read_pixels"),
+            size: (padded_row_size * height) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_size),
+                    rows_per_image: None,
+                },
+            },
+            texture.size(),
+        );
+        self.queue.submit([encoder.finish()]);
+
+        let (sender, receiver) = flume::bounded(1);
+        let slice = buffer.slice(..);
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver.recv_async().await??;
+
+        let mapped = slice.get_mapped_range();
+        let pixels = mapped
+            .chunks(padded_row_size as usize)
+            .flat_map(|row| &row[..row_size as usize])
+            .copied()
+            .collect();
+        drop(mapped);
+        buffer.unmap();
+
+        Ok(pixels)
     }
 }
 
+async fn request_device(
+    instance: &wgpu::Instance,
+    surface: Option<&wgpu::Surface<'_>>,
+) -> anyhow::Result<(wgpu::Adapter, wgpu::Device, wgpu::Queue)> {
+    let adapter = instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            compatible_surface: surface,
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        })
+        .await
+        .context("No valid adapter")?;
+
+    println!("info: {:?}", adapter.get_info());
+
+    let (device, queue) = adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                label: None,
+                required_features: wgpu::Features::empty(),
+                required_limits: wgpu::Limits::downlevel_defaults(),
+            },
+            None,
+        )
+        .await?;
+
+    Ok((adapter, device, queue))
+}
+
+fn create_target_texture(device: &wgpu::Device, width: u32, height: u32) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("headless_target"),
+        size: wgpu::Extent3d {
+            width: width.max(1),
+            height: height.max(1),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
 pub struct Frame {
-    pub target: wgpu::SurfaceTexture,
+    pub view: wgpu::TextureView,
     pub encoder: wgpu::CommandEncoder,
 }
@@ -0,0 +1,78 @@
+use crate::context::Context;
+
+/// Clears the current viewport of a render pass, color to black and depth
+/// to the clear value of a projection. Load ops always clear the whole
+/// attachment, this is for views drawn on top of others.
+pub struct ClearPipeline {
+    depth_zero: wgpu::RenderPipeline,
+    depth_one: wgpu::RenderPipeline,
+}
+
+impl ClearPipeline {
+    pub fn new(
+        context: &Context,
+        surface_format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+    ) -> Self {
+        let module = context
+            .device
+            .create_shader_module(wgpu::include_wgsl!("clear.wgsl"));
+        let pipeline = |entry_point| {
+            create_pipeline(context, &module, entry_point, surface_format, depth_format)
+        };
+        Self {
+            depth_zero: pipeline("vs_depth_zero"),
+            depth_one: pipeline("vs_depth_one"),
+        }
+    }
+
+    /// `depth` is 0 or 1, see [`crate::resources::camera::Projection::depth_clear`].
+    pub fn draw<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>, depth: f32) {
+        let pipeline = if depth < 0.5 {
+            &self.depth_zero
+        } else {
+            &self.depth_one
+        };
+        pass.set_pipeline(pipeline);
+        pass.draw(0..3, 0..1);
+    }
+}
+
+fn create_pipeline(
+    context: &Context,
+    module: &wgpu::ShaderModule,
+    entry_point: &str,
+    surface_format: wgpu::TextureFormat,
+    depth_format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    context
+        .device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("ClearPipeline"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module,
+                entry_point,
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: depth_format,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: Default::default(),
+            fragment: Some(wgpu::FragmentState {
+                module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: Default::default(),
+        })
+}
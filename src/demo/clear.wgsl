@@ -0,0 +1,21 @@
+// A triangle covering the viewport, at the depth a pass would be cleared to.
+
+fn corner(index: u32, depth: f32) -> vec4<f32> {
+    let uv = vec2(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4(uv * 2.0 - 1.0, depth, 1.0);
+}
+
+@vertex
+fn vs_depth_zero(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    return corner(index, 0.0);
+}
+
+@vertex
+fn vs_depth_one(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    return corner(index, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4(0.0, 0.0, 0.0, 1.0);
+}
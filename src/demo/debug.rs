@@ -22,8 +22,17 @@ impl Vertex {
     };
 }
 
+#[cfg(feature = "hot-reload")]
+pub const SHADER_PATH: &str = "src/demo/debug.wgsl";
+
 pub struct DebugPipeline {
-    pipeline: wgpu::RenderPipeline,
+    /// One per depth test in use, cameras with different projections can
+    /// share a frame.
+    pipelines: Vec<(wgpu::CompareFunction, wgpu::RenderPipeline)>,
+    layout: wgpu::PipelineLayout,
+    module: wgpu::ShaderModule,
+    surface_format: wgpu::TextureFormat,
+    depth_format: wgpu::TextureFormat,
     vertices: buffer::CpuBuffer<Vertex>,
 }
 
@@ -45,43 +54,82 @@ impl DebugPipeline {
 
         let module = context
             .device
-            .create_shader_module(wgpu::include_wgsl!("debug.wgsl"));
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("debug.wgsl"),
+                source: wgpu::ShaderSource::Wgsl(
+                    camera::CameraData::with_wgsl(include_str!("debug.wgsl")).into(),
+                ),
+            });
+
+        Self {
+            pipelines: Vec::new(),
+            layout,
+            module,
+            surface_format,
+            depth_format,
+            vertices,
+        }
+    }
 
-        let pipeline = context
+    /// Builds the pipeline for `depth_compare` unless it exists already.
+    /// Has to be called before drawing with it.
+    pub fn prepare(&mut self, context: &Context, depth_compare: wgpu::CompareFunction) {
+        if self.pipelines.iter().any(|(compare, _)| *compare == depth_compare) {
+            return;
+        }
+        let pipeline = create_pipeline(
+            context,
+            &self.layout,
+            &self.module,
+            self.surface_format,
+            self.depth_format,
+            depth_compare,
+        );
+        self.pipelines.push((depth_compare, pipeline));
+    }
+
+    /// Rebuilds the pipelines from new shader source. If the source doesn't
+    /// compile the old pipelines are kept and the error is returned.
+    #[cfg(feature = "hot-reload")]
+    pub fn reload_shader(&mut self, context: &Context, source: &str) -> anyhow::Result<()> {
+        use pollster::FutureExt;
+
+        context.device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let module = context
             .device
-            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("DebugPipeline"),
-                layout: Some(&layout),
-                vertex: wgpu::VertexState {
-                    module: &module,
-                    entry_point: "vs_main",
-                    buffers: &[Vertex::VERTEX],
-                },
-                primitive: PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::LineList,
-                    ..Default::default()
-                },
-                depth_stencil: Some(wgpu::DepthStencilState {
-                    format: depth_format,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::Less,
-                    stencil: wgpu::StencilState::default(),
-                    bias: wgpu::DepthBiasState::default(),
-                }),
-                multisample: Default::default(),
-                fragment: Some(wgpu::FragmentState {
-                    module: &module,
-                    entry_point: "fs_main",
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: surface_format,
-                        blend: None,
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                }),
-                multiview: Default::default(),
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(SHADER_PATH),
+                source: wgpu::ShaderSource::Wgsl(camera::CameraData::with_wgsl(source).into()),
             });
+        let pipelines: Vec<_> = self
+            .pipelines
+            .iter()
+            .map(|(compare, _)| {
+                let pipeline = create_pipeline(
+                    context,
+                    &self.layout,
+                    &module,
+                    self.surface_format,
+                    self.depth_format,
+                    *compare,
+                );
+                (*compare, pipeline)
+            })
+            .collect();
 
-        Self { pipeline, vertices }
+        if let Some(e) = context.device.pop_error_scope().block_on() {
+            anyhow::bail!("{e}");
+        }
+
+        self.pipelines = pipelines;
+        self.module = module;
+
+        Ok(())
+    }
+
+    pub fn clear_lines(&mut self) {
+        self.vertices.clear();
     }
 
     pub fn line_batch<'a>(&'a mut self, context: &'a Context) -> LineBatch<'a> {
@@ -90,14 +138,70 @@ impl DebugPipeline {
         }
     }
 
-    pub(crate) fn draw<'a: 'b, 'b>(&'a self, pass: &'b mut wgpu::RenderPass<'a>, camera_binding: &'a camera::CameraBinding) {
-        pass.set_pipeline(&self.pipeline);
-        pass.set_bind_group(0, camera_binding.bind_group(), &[]);
+    /// Panics if [`DebugPipeline::prepare`] wasn't called for
+    /// `depth_compare`.
+    pub(crate) fn draw<'a: 'b, 'b>(
+        &'a self,
+        pass: &'b mut wgpu::RenderPass<'a>,
+        camera_binding: &'a camera::CameraBinding,
+        camera_slot: usize,
+        depth_compare: wgpu::CompareFunction,
+    ) {
+        let (_, pipeline) = self
+            .pipelines
+            .iter()
+            .find(|(compare, _)| *compare == depth_compare)
+            .expect("DebugPipeline::prepare wasn't called");
+        pass.set_pipeline(pipeline);
+        camera_binding.set(pass, 0, camera_slot);
         pass.set_vertex_buffer(0, self.vertices.buffer.slice(..));
         pass.draw(0..self.vertices.len(), 0..1);
     }
 }
 
+fn create_pipeline(
+    context: &Context,
+    layout: &wgpu::PipelineLayout,
+    module: &wgpu::ShaderModule,
+    surface_format: wgpu::TextureFormat,
+    depth_format: wgpu::TextureFormat,
+    depth_compare: wgpu::CompareFunction,
+) -> wgpu::RenderPipeline {
+    context
+        .device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("DebugPipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module,
+                entry_point: "vs_main",
+                buffers: &[Vertex::VERTEX],
+            },
+            primitive: PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineList,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: depth_format,
+                depth_write_enabled: true,
+                depth_compare,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: Default::default(),
+            fragment: Some(wgpu::FragmentState {
+                module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: Default::default(),
+        })
+}
+
 pub struct LineBatch<'a> {
     batch: buffer::BufferBatch<'a, Vertex>,
 }
@@ -110,6 +214,13 @@ impl<'a> LineBatch<'a> {
         self
     }
 
+    pub fn push_cross(&mut self, center: glam::Vec3, size: f32, color: glam::Vec3) -> &mut Self {
+        let half = size * 0.5;
+        self.push(center - glam::Vec3::X * half, center + glam::Vec3::X * half, color)
+            .push(center - glam::Vec3::Y * half, center + glam::Vec3::Y * half, color)
+            .push(center - glam::Vec3::Z * half, center + glam::Vec3::Z * half, color)
+    }
+
     pub fn push_axes(&mut self, length: f32) -> &mut Self {
         self.push(
             glam::vec3(0.0, 0.0, 0.0),
@@ -1,6 +1,4 @@
-struct Camera {
-    view_proj: mat4x4<f32>,
-}
+// CameraData is prepended from camera::CameraData::WGSL.
 
 struct Vertex {
     @location(0)
@@ -18,7 +16,7 @@ struct VsOut {
 
 @group(0)
 @binding(0)
-var<uniform> camera: Camera;
+var<uniform> camera: CameraData;
 
 @vertex
 fn vs_main(in: Vertex) -> VsOut {
@@ -1,78 +1,600 @@
+mod clear;
 mod debug;
+mod picking;
 
 use std::f32::consts::PI;
 
+use pollster::FutureExt;
 use wgpu::RenderPassDescriptor;
-use winit::{keyboard::{PhysicalKey, KeyCode}, event::DeviceId};
+use winit::{keyboard::PhysicalKey, event::DeviceId};
 
 use crate::{
+    config::Config,
     context::{Context, Frame},
-    resources::{camera, texture},
+    input::{self, gamepad::GamepadId, touch::Gesture, InputMap},
+    resources::{
+        bounds::{Aabb, Plane},
+        camera::{
+            self,
+            path::{CameraPath, Keyframe},
+            PixelRect, Viewport,
+        },
+        picking::{Pick, PickingTarget},
+        texture,
+    },
+    window,
 };
 
 pub enum Event {}
 
+/// The debug axes drawn at the origin.
+const SCENE_BOUNDS: Aabb = Aabb::new(glam::Vec3::ZERO, glam::Vec3::splat(0.5));
+/// Object IDs in the picking pass, markers follow in order.
+const SCENE_ID: u32 = 1;
+/// Older markers are dropped, the debug lines have a fixed capacity.
+const MAX_MARKERS: usize = 8;
+const MARKER_SIZE: f32 = 0.1;
+const FOVY: f32 = PI / 4.0;
+const NEAR: f32 = 0.1;
+const FAR: f32 = 100.0;
+/// Written when path recording stops, read when playing without a path.
+const CAMERA_PATH_FILE: &str = "user:camera_path.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CameraMode {
+    Fly,
+    Orbit,
+}
+
+/// Indices into `Demo::cameras`, which are also their slots in the camera
+/// binding. Only `MAIN` is controlled, the others look at the scene from
+/// fixed positions.
+const MAIN: usize = 0;
+const OPPOSITE: usize = 1;
+const TOP: usize = 2;
+const FRONT: usize = 3;
+const SIDE: usize = 4;
+const CAMERA_COUNT: usize = 5;
+
+/// How the surface is split between the cameras.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layout {
+    Single,
+    SplitScreen,
+    PictureInPicture,
+    Quad,
+}
+
+impl Layout {
+    /// Cameras and where they draw, back to front. The main camera always
+    /// comes first.
+    fn views(self) -> &'static [(usize, Viewport)] {
+        const SINGLE: [(usize, Viewport); 1] = [(MAIN, Viewport::FULL)];
+        const SPLIT_SCREEN: [(usize, Viewport); 2] = [
+            (MAIN, Viewport::new(0.0, 0.0, 0.5, 1.0)),
+            (OPPOSITE, Viewport::new(0.5, 0.0, 0.5, 1.0)),
+        ];
+        const PICTURE_IN_PICTURE: [(usize, Viewport); 2] = [
+            (MAIN, Viewport::FULL),
+            (TOP, Viewport::new(0.7, 0.05, 0.25, 0.25)),
+        ];
+        const QUAD: [(usize, Viewport); 4] = [
+            (MAIN, Viewport::new(0.0, 0.0, 0.5, 0.5)),
+            (TOP, Viewport::new(0.5, 0.0, 0.5, 0.5)),
+            (FRONT, Viewport::new(0.0, 0.5, 0.5, 0.5)),
+            (SIDE, Viewport::new(0.5, 0.5, 0.5, 0.5)),
+        ];
+        match self {
+            Self::Single => &SINGLE,
+            Self::SplitScreen => &SPLIT_SCREEN,
+            Self::PictureInPicture => &PICTURE_IN_PICTURE,
+            Self::Quad => &QUAD,
+        }
+    }
+
+    /// The camera drawing at a position in physical pixels. Later views
+    /// are drawn over earlier ones, so they are hit first.
+    fn view_at(self, (width, height): (u32, u32), x: f64, y: f64) -> Option<usize> {
+        self.views()
+            .iter()
+            .rev()
+            .find(|(_, viewport)| viewport.pixels(width, height).contains(x, y))
+            .map(|(slot, _)| *slot)
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Self::Single => Self::SplitScreen,
+            Self::SplitScreen => Self::PictureInPicture,
+            Self::PictureInPicture => Self::Quad,
+            Self::Quad => Self::Single,
+        }
+    }
+}
+
 pub struct Demo {
     #[allow(dead_code)]
     events: Vec<Event>,
     depth_texture: texture::Texture,
     debug: debug::DebugPipeline,
-    #[allow(dead_code)]
-    camera: camera::Camera,
+    clear: clear::ClearPipeline,
+    picking: PickingTarget,
+    picking_pipeline: picking::PickingPipeline,
+    cameras: [camera::Camera; CAMERA_COUNT],
     camera_binding: camera::CameraBinding,
+    camera_mode: CameraMode,
+    layout: Layout,
+    /// Of the surface, in physical pixels.
+    size: (u32, u32),
+    /// Where the main camera draws.
+    main_rect: PixelRect,
+    /// The cameras and the picking target need resizing to the layout.
+    layout_dirty: bool,
+    fly: camera::fly::FlyController,
+    orbit: camera::orbit::OrbitController,
+    window_commands: Vec<window::Command>,
+    scale_factor: f64,
+    /// Sum of the update `dt`s, so replays see the same time.
+    time: f32,
+    /// In physical pixels, `None` while outside the window.
+    cursor: Option<(f64, f64)>,
+    markers: Vec<glam::Vec3>,
+    camera_path: CameraPath,
+    /// When recording of `camera_path` started, keyframes are timed from it.
+    path_recording: Option<f32>,
+    /// When playback of `camera_path` started, it drives the main camera
+    /// instead of the controllers meanwhile.
+    path_playback: Option<f32>,
+    /// Writes the last recorded path without holding up the frame.
+    path_saving: Option<std::thread::JoinHandle<()>>,
+    /// The lines and the pickable boxes need rebuilding.
+    lines_dirty: bool,
+    /// Around the axes and the markers.
+    lines_bounds: Aabb,
     pub running: bool,
 }
 
 impl Demo {
-    pub fn new(context: &Context, width: u32, height: u32) -> anyhow::Result<Self> {
+    pub fn new(context: &Context, width: u32, height: u32, scale_factor: f64) -> anyhow::Result<Self> {
         let depth_texture = texture::Texture::depth_texture(&context.device, width, height);
 
-        let camera = camera::Camera::look_at(
-            glam::vec3(1.0, 1.0, 2.0),
-            glam::vec3(0.0, 0.0, 0.0),
-            width as _,
-            height as _,
-            PI / 4.0,
-            0.1,
-            100.0,
-        );
+        let perspective = |eye: glam::Vec3, target: glam::Vec3| {
+            camera::Camera::look_at(
+                eye,
+                target,
+                width as _,
+                height as _,
+                FOVY,
+                NEAR,
+                FAR,
+            )
+        };
+        let orthographic = |direction: glam::Vec3| {
+            let target = SCENE_BOUNDS.center();
+            let mut camera = perspective(target - direction * 5.0, target);
+            camera.set_projection(camera::Projection::Orthographic {
+                size: 1.5,
+                zoom: 1.0,
+                near: NEAR,
+                far: FAR,
+            });
+            camera
+        };
+        let cameras = [
+            perspective(glam::vec3(1.0, 1.0, 2.0), glam::Vec3::ZERO),
+            perspective(glam::vec3(-1.5, 1.0, -1.0), SCENE_BOUNDS.center()),
+            orthographic(glam::Vec3::NEG_Y),
+            orthographic(glam::Vec3::NEG_Z),
+            orthographic(glam::Vec3::NEG_X),
+        ];
 
         let camera_binder = camera::CameraBinder::new(&context.device);
-        let camera_binding = camera_binder.bind(&context.device, &camera);
+        let camera_binding = camera_binder.bind(&context.device, CAMERA_COUNT);
 
-        let mut debug = debug::DebugPipeline::new(
+        let debug = debug::DebugPipeline::new(
             context,
             &camera_binder,
             context.surface_format(),
             depth_texture.format(),
         );
+        let clear = clear::ClearPipeline::new(
+            context,
+            context.surface_format(),
+            depth_texture.format(),
+        );
 
-        { 
-            let mut batch = debug.line_batch(context);
-            batch.push_axes(0.5);
-        }
+        let picking = PickingTarget::new(context, width, height);
+        let picking_pipeline = picking::PickingPipeline::new(
+            context,
+            &camera_binder,
+            cameras[MAIN].projection().depth_compare(),
+        );
 
-        Ok(Self {
+        let mut demo = Self {
             events: Vec::new(),
             depth_texture,
             debug,
-            camera,
+            clear,
+            picking,
+            picking_pipeline,
+            cameras,
             camera_binding,
+            camera_mode: CameraMode::Fly,
+            layout: Layout::Single,
+            size: (width, height),
+            main_rect: Viewport::FULL.pixels(width, height),
+            layout_dirty: true,
+            fly: Default::default(),
+            orbit: Default::default(),
+            window_commands: Vec::new(),
+            scale_factor,
+            time: 0.0,
+            cursor: None,
+            markers: Vec::new(),
+            camera_path: CameraPath::default(),
+            path_recording: None,
+            path_playback: None,
+            path_saving: None,
+            lines_dirty: true,
+            lines_bounds: SCENE_BOUNDS,
             running: true,
-        })
+        };
+        demo.apply_layout(context);
+        Ok(demo)
+    }
+
+    /// Sizes the cameras to their viewports. Picks and cursor rays are
+    /// relative to the main camera's viewport.
+    fn apply_layout(&mut self, context: &Context) {
+        self.layout_dirty = false;
+        let (width, height) = self.size;
+        for (slot, viewport) in self.layout.views() {
+            let rect = viewport.pixels(width, height);
+            self.cameras[*slot].resize(rect.width, rect.height);
+            if *slot == MAIN {
+                self.main_rect = rect;
+                self.picking.resize(context, rect.width.max(1), rect.height.max(1));
+            }
+        }
+    }
+
+    /// The cursor relative to the main camera's viewport, `None` while
+    /// it's outside or over a view drawn on top of it.
+    fn main_cursor(&self) -> Option<(f64, f64)> {
+        let (x, y) = self.cursor?;
+        let rect = self.main_rect;
+        (self.layout.view_at(self.size, x, y) == Some(MAIN))
+            .then_some((x - rect.x as f64, y - rect.y as f64))
+    }
+
+    pub(crate) fn configure(&mut self, config: &Config) {
+        self.fly.settings = config.fly_camera;
+        self.orbit.settings = config.orbit_camera;
+    }
+
+    pub fn update(&mut self, input: &InputMap, dt: f32) {
+        self.time += dt;
+
+        if input.just_pressed(input::QUIT) {
+            self.stop_path_recording();
+            self.running = false;
+        }
+
+        if input.just_pressed(input::SWITCH_CAMERA) {
+            self.camera_mode = match self.camera_mode {
+                CameraMode::Fly => {
+                    let target = self.orbit.target();
+                    self.orbit.look_from(self.cameras[MAIN].eye(), target);
+                    self.set_mouse_look(false);
+                    CameraMode::Orbit
+                }
+                CameraMode::Orbit => {
+                    self.fly.stop();
+                    CameraMode::Fly
+                }
+            };
+        }
+
+        if input.just_pressed(input::CYCLE_PROJECTION) {
+            self.cycle_projection();
+        }
+
+        if input.just_pressed(input::CYCLE_LAYOUT) {
+            self.layout = self.layout.next();
+            log::info!("Layout: {:?}", self.layout);
+            self.layout_dirty = true;
+        }
+
+        if input.just_pressed(input::PLACE_MARKER) {
+            self.place_marker();
+        }
+
+        if input.just_pressed(input::PICK) {
+            if let Some((x, y)) = self.main_cursor() {
+                self.picking.request(x, y);
+            }
+        }
+
+        if input.just_pressed(input::RECORD_PATH) {
+            if self.path_recording.is_some() {
+                self.stop_path_recording();
+            } else {
+                self.stop_path_playback();
+                self.camera_path.clear();
+                self.path_recording = Some(self.time);
+                log::info!("Recording camera path");
+            }
+        }
+
+        if input.just_pressed(input::CAPTURE_KEYFRAME) {
+            if let Some(start) = self.path_recording {
+                let keyframe = Keyframe::from_camera(&self.cameras[MAIN], self.time - start);
+                self.camera_path.insert(keyframe);
+                log::info!("Captured keyframe {}", self.camera_path.keyframes().len());
+            }
+        }
+
+        if input.just_pressed(input::PLAY_PATH) {
+            if self.path_playback.is_some() {
+                self.stop_path_playback();
+            } else {
+                self.start_path_playback();
+            }
+        }
+
+        if let Some(start) = self.path_playback {
+            let time = self.time - start;
+            if let Some(keyframe) = self.camera_path.sample(time) {
+                keyframe.apply(&mut self.cameras[MAIN]);
+            }
+            if self.camera_path.is_finished(time) {
+                self.stop_path_playback();
+            }
+            return;
+        }
+
+        match self.camera_mode {
+            CameraMode::Fly => {
+                if input.just_pressed(input::TOGGLE_MOUSE_LOOK) {
+                    self.set_mouse_look(!self.fly.mouse_look);
+                }
+                self.fly.update(&mut self.cameras[MAIN], input, dt);
+            }
+            CameraMode::Orbit => {
+                if input.just_pressed(input::FRAME_SCENE) {
+                    self.orbit
+                        .frame_bounds(SCENE_BOUNDS.min, SCENE_BOUNDS.max, &mut self.cameras[MAIN]);
+                }
+                self.orbit.update(&mut self.cameras[MAIN], input, dt);
+            }
+        }
+    }
+
+    pub(crate) fn stop_path_recording(&mut self) {
+        if self.path_recording.take().is_none() {
+            return;
+        }
+        let count = self.camera_path.keyframes().len();
+        log::info!("Recorded {count} keyframes");
+        // One save at a time, so an older path never overwrites a newer one.
+        self.finish_path_saving();
+        let camera_path = self.camera_path.clone();
+        self.path_saving = Some(std::thread::spawn(move || {
+            if let Err(e) = camera_path.save(CAMERA_PATH_FILE).block_on() {
+                log::error!("Unable to save {CAMERA_PATH_FILE}: {e}");
+            }
+        }));
+    }
+
+    /// Waits for the camera path to be written, `lib::run` calls this
+    /// before exiting.
+    pub(crate) fn finish_path_saving(&mut self) {
+        if let Some(saving) = self.path_saving.take() {
+            let _ = saving.join();
+        }
+    }
+
+    /// Loads the path saved by an earlier run, which plays until a new one
+    /// is recorded. Call this before the first update. Loading it on demand
+    /// would start playback on a different frame in every replay.
+    pub(crate) async fn load_camera_path(&mut self) {
+        let Some(real_path) = crate::resources::vfs::get().real_path(CAMERA_PATH_FILE) else {
+            return;
+        };
+        if !real_path.exists() {
+            return;
+        }
+        match CameraPath::load(CAMERA_PATH_FILE).await {
+            Ok(camera_path) => self.camera_path = camera_path,
+            Err(e) => log::warn!("Unable to load {CAMERA_PATH_FILE}: {e}"),
+        }
+    }
+
+    /// Plays the last recorded path, or the one saved by an earlier run.
+    fn start_path_playback(&mut self) {
+        self.stop_path_recording();
+        if self.camera_path.is_empty() {
+            log::warn!("No camera path to play, record one first");
+            return;
+        }
+        log::info!("Playing camera path ({:?})", self.camera_path.mode);
+        self.path_playback = Some(self.time);
+    }
+
+    /// Hands the main camera back to the controllers where playback left it.
+    fn stop_path_playback(&mut self) {
+        if self.path_playback.take().is_none() {
+            return;
+        }
+        match self.camera_mode {
+            CameraMode::Fly => self.fly.stop(),
+            CameraMode::Orbit => {
+                let target = self.orbit.target();
+                self.orbit.look_from(self.cameras[MAIN].eye(), target);
+            }
+        }
+    }
+
+    /// Perspective, then reverse-Z, then orthographic sized to show what
+    /// the perspective camera showed at the orbit target.
+    fn cycle_projection(&mut self) {
+        let projection = match self.cameras[MAIN].projection() {
+            camera::Projection::Perspective { .. } => camera::Projection::ReverseInfinite {
+                fovy: FOVY,
+                near: NEAR,
+            },
+            camera::Projection::ReverseInfinite { .. } => {
+                let distance = self.cameras[MAIN].eye().distance(self.orbit.target());
+                camera::Projection::Orthographic {
+                    size: 2.0 * distance * (FOVY * 0.5).tan(),
+                    zoom: 1.0,
+                    near: NEAR,
+                    far: FAR,
+                }
+            }
+            camera::Projection::Orthographic { .. } => camera::Projection::Perspective {
+                fovy: FOVY,
+                near: NEAR,
+                far: FAR,
+            },
+        };
+        log::info!("Projection: {projection:?}");
+        self.cameras[MAIN].set_projection(projection);
+    }
+
+    /// Puts a marker where the cursor points at the scene bounds or the
+    /// ground, whichever is closer.
+    fn place_marker(&mut self) {
+        let Some((x, y)) = self.main_cursor() else {
+            return;
+        };
+        let ray = self.cameras[MAIN].cursor_ray(x, y);
+        let ground = Plane::from_point_normal(glam::Vec3::ZERO, glam::Vec3::Y);
+        let hit = [ray.intersect_aabb(&SCENE_BOUNDS), ray.intersect_plane(&ground)]
+            .into_iter()
+            .flatten()
+            .reduce(f32::min);
+        let Some(t) = hit else {
+            return;
+        };
+        if self.markers.len() == MAX_MARKERS {
+            self.markers.remove(0);
+        }
+        self.markers.push(ray.at(t));
+        self.lines_dirty = true;
+    }
+
+    /// Called once the picking pass has been read back, a few frames after
+    /// [`input::PICK`].
+    fn on_pick(&mut self, pick: Pick) {
+        let object = match pick.id {
+            SCENE_ID => "the scene".to_string(),
+            id if id > SCENE_ID => format!("marker {}", id - SCENE_ID - 1),
+            _ => "nothing".to_string(),
+        };
+        match pick.position {
+            Some(position) => log::info!("Picked {object} at {position}"),
+            None => log::info!("Picked {object}"),
+        }
+    }
+
+    /// The cursor mode the window applied. Mouse look is turned off again if
+    /// the cursor couldn't be grabbed.
+    pub(crate) fn on_cursor_mode(&mut self, mode: window::CursorMode) {
+        let grabbed = matches!(
+            mode,
+            window::CursorMode::Locked | window::CursorMode::Confined
+        );
+        if self.fly.mouse_look && !grabbed {
+            log::warn!("Unable to grab the cursor for mouse look");
+            self.set_mouse_look(false);
+        }
+    }
+
+    fn set_mouse_look(&mut self, mouse_look: bool) {
+        if self.fly.mouse_look == mouse_look {
+            return;
+        }
+        self.fly.mouse_look = mouse_look;
+        let mode = if mouse_look {
+            window::CursorMode::Locked
+        } else {
+            window::CursorMode::Free
+        };
+        self.request_window(window::Command::SetCursorMode(mode));
     }
 
     pub fn resize(&mut self, context: &Context, width: u32, height: u32) {
         self.depth_texture.resize(context, width, height);
+        self.size = (width, height);
+        self.apply_layout(context);
+    }
+
+    /// Physical pixels per logical pixel, use it to size UI and lines.
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
     }
 
-    pub fn render(&mut self, frame: &mut Frame, _context: &Context) {
-        let view = frame.target.texture.create_view(&Default::default());
+    pub(crate) fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, context: &Context) {
+        if self.layout_dirty {
+            self.apply_layout(context);
+        }
+        let views = self.layout.views();
+        for (slot, _) in views {
+            self.camera_binding
+                .update(&context.queue, *slot, &self.cameras[*slot], self.time);
+            self.debug
+                .prepare(context, self.cameras[*slot].projection().depth_compare());
+        }
+        if self.lines_dirty {
+            self.lines_dirty = false;
+            self.debug.clear_lines();
+            let mut batch = self.debug.line_batch(context);
+            batch.push_axes(0.5);
+            for marker in &self.markers {
+                batch.push_cross(*marker, MARKER_SIZE, glam::vec3(1.0, 1.0, 0.0));
+            }
+            let markers = Aabb::from_points(self.markers.iter().copied())
+                .map(|aabb| Aabb::new(aabb.min - MARKER_SIZE, aabb.max + MARKER_SIZE));
+            self.lines_bounds =
+                markers.map_or(SCENE_BOUNDS, |markers| markers.union(&SCENE_BOUNDS));
+
+            let half = glam::Vec3::splat(MARKER_SIZE * 0.5);
+            let markers = self
+                .markers
+                .iter()
+                .map(|marker| Aabb::new(*marker - half, *marker + half));
+            let boxes = std::iter::once(SCENE_BOUNDS).chain(markers).zip(SCENE_ID..);
+            self.picking_pipeline.set_boxes(context, boxes);
+        }
+
+        let projection = *self.cameras[MAIN].projection();
+        self.picking_pipeline
+            .set_depth_compare(context, projection.depth_compare());
+
+        if let Some(pick) = self.picking.poll(context) {
+            self.on_pick(pick);
+        }
+        if self.picking.needs_pass() {
+            let mut encoder = context.device.create_command_encoder(&Default::default());
+            {
+                let mut pass =
+                    self.picking
+                        .begin_pass(context, &mut encoder, &self.cameras[MAIN]);
+                self.picking_pipeline
+                    .draw(&mut pass, &self.camera_binding, MAIN);
+            }
+            self.picking.finish(context, encoder, &self.cameras[MAIN]);
+        }
 
         let mut pass = frame.encoder.begin_render_pass(&RenderPassDescriptor {
             label: None,
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &view,
+                view: &frame.view,
                 resolve_target: None,
                 ops: wgpu::Operations {
                     load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
@@ -82,7 +604,7 @@ impl Demo {
             depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
                 view: self.depth_texture.view(),
                 depth_ops: Some(wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(1.0),
+                    load: wgpu::LoadOp::Clear(projection.depth_clear()),
                     store: wgpu::StoreOp::Store,
                 }),
                 stencil_ops: None,
@@ -90,33 +612,130 @@ impl Demo {
             ..Default::default()
         });
 
-        self.debug.draw(&mut pass, &self.camera_binding);
+        for (i, (slot, viewport)) in views.iter().enumerate() {
+            let rect = viewport.pixels(self.size.0, self.size.1);
+            if rect.is_empty() {
+                continue;
+            }
+            rect.apply(&mut pass);
+
+            let camera = &self.cameras[*slot];
+            let projection = camera.projection();
+            // The first view was cleared with the pass.
+            if i > 0 {
+                self.clear.draw(&mut pass, projection.depth_clear());
+            }
+            if camera.frustum().intersects_aabb(&self.lines_bounds) {
+                self.debug.draw(
+                    &mut pass,
+                    &self.camera_binding,
+                    *slot,
+                    projection.depth_compare(),
+                );
+            }
+        }
     }
 
     pub fn close(&mut self) -> bool {
+        self.stop_path_recording();
+        self.finish_path_saving();
         true
     }
 
-    pub(crate) fn on_cursor_moved(&mut self, _x: f64, _y: f64) {}
+    /// Queues a window operation, `lib::run` applies them once per frame.
+    pub fn request_window(&mut self, command: window::Command) {
+        self.window_commands.push(command);
+    }
+
+    pub(crate) fn take_window_commands(&mut self) -> Vec<window::Command> {
+        std::mem::take(&mut self.window_commands)
+    }
+
+    pub(crate) fn on_cursor_moved(&mut self, x: f64, y: f64) {
+        self.cursor = Some((x, y));
+    }
 
     pub(crate) fn on_cursor_entered(&mut self) {}
 
-    pub(crate) fn on_cursor_left(&mut self) {}
+    pub(crate) fn on_cursor_left(&mut self) {
+        self.cursor = None;
+    }
 
     pub(crate) fn on_device_added(&mut self, _device_id: DeviceId) {}
 
     pub(crate) fn on_device_removed(&mut self, _device_id: DeviceId) {}
 
+    pub(crate) fn on_gamepad_connected(&mut self, _id: GamepadId, _name: &str) {}
+
+    pub(crate) fn on_gamepad_disconnected(&mut self, _id: GamepadId) {}
+
     pub(crate) fn on_mouse_scoll(&mut self, _x: f32, _y: f32) {}
 
+    pub(crate) fn on_gesture(&mut self, _gesture: Gesture) {}
+
     pub(crate) fn on_axis(&mut self, _axis: u32, _value: f64) {}
 
     pub(crate) fn on_button(&mut self, _button: u32, _pressed: bool) {}
 
-    pub(crate) fn on_key(&mut self, physical_key: PhysicalKey, pressed: bool) {
-        match (physical_key, pressed) {
-            (PhysicalKey::Code(KeyCode::Escape), true) => self.running = false,
-            _ => {}
+    pub(crate) fn on_key(&mut self, _physical_key: PhysicalKey, _pressed: bool) {}
+
+    /// Only shaders for now. Textures can't be reloaded yet: nothing loads
+    /// one from a file, every texture is a render target sized in code.
+    #[cfg(feature = "hot-reload")]
+    pub fn watched_assets(&self) -> &[&'static str] {
+        &[debug::SHADER_PATH, picking::SHADER_PATH]
+    }
+
+    #[cfg(feature = "hot-reload")]
+    pub async fn reload_asset(&mut self, context: &Context<'_>, path: &str) -> anyhow::Result<()> {
+        if path == debug::SHADER_PATH {
+            let source = crate::resources::fs::load_text(path).await?;
+            self.debug.reload_shader(context, &source)?;
+        } else if path == picking::SHADER_PATH {
+            let source = crate::resources::fs::load_text(path).await?;
+            self.picking_pipeline.reload_shader(context, &source)?;
         }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::{camera::path::PlayMode, vfs};
+
+    #[test]
+    fn camera_paths_are_user_data() {
+        let mut path = CameraPath::new(PlayMode::Once);
+        for (time, x) in [(1.0, 1.0), (0.5, -2.0)] {
+            path.insert(Keyframe {
+                time,
+                eye: glam::Vec3::splat(x),
+                orientation: glam::Quat::from_rotation_y(x),
+                fovy: Some(FOVY),
+            });
+        }
+
+        // The vfs used by tests has a temporary user dir.
+        path.save(CAMERA_PATH_FILE).block_on().unwrap();
+        let real_path = vfs::get().user_dir().join("camera_path.json");
+        assert!(real_path.is_file());
+        let loaded = CameraPath::load(CAMERA_PATH_FILE).block_on().unwrap();
+        assert_eq!(loaded.keyframes(), path.keyframes());
+        assert_eq!(loaded.mode, PlayMode::Once);
+    }
+
+    #[test]
+    fn insets_hide_the_main_view() {
+        let size = (1000, 1000);
+        let layout = Layout::PictureInPicture;
+        assert_eq!(layout.view_at(size, 100.0, 500.0), Some(MAIN));
+        // Inside the inset in the top right corner.
+        assert_eq!(layout.view_at(size, 800.0, 100.0), Some(TOP));
+        assert_eq!(layout.view_at(size, 1000.0, 100.0), None);
+
+        assert_eq!(Layout::Quad.view_at(size, 100.0, 100.0), Some(MAIN));
+        assert_eq!(Layout::Quad.view_at(size, 600.0, 600.0), Some(SIDE));
+        assert_eq!(Layout::SplitScreen.view_at(size, 600.0, 100.0), Some(OPPOSITE));
     }
 }
@@ -0,0 +1,307 @@
+use std::mem;
+
+use wgpu::util::DeviceExt;
+
+use crate::{
+    context::Context,
+    resources::{
+        bounds::Aabb,
+        buffer,
+        camera::{self, CameraBinder},
+        picking,
+    },
+};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Instance {
+    min: glam::Vec3,
+    max: glam::Vec3,
+    id: u32,
+}
+
+impl Instance {
+    pub const VERTEX: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+        array_stride: mem::size_of::<Self>() as u64,
+        step_mode: wgpu::VertexStepMode::Instance,
+        attributes: &wgpu::vertex_attr_array![
+            0 => Float32x3,
+            1 => Float32x3,
+            2 => Uint32,
+        ],
+    };
+}
+
+/// Two triangles per face of a box, with the corners numbered like the
+/// shader does.
+#[rustfmt::skip]
+const BOX_INDICES: [u16; 36] = [
+    0, 2, 6, 0, 6, 4,
+    1, 5, 7, 1, 7, 3,
+    0, 4, 5, 0, 5, 1,
+    2, 3, 7, 2, 7, 6,
+    0, 1, 3, 0, 3, 2,
+    4, 6, 7, 4, 7, 5,
+];
+
+#[cfg(feature = "hot-reload")]
+pub const SHADER_PATH: &str = "src/demo/picking.wgsl";
+
+/// Draws boxes with object IDs into a [`picking::PickingTarget`].
+pub struct PickingPipeline {
+    pipeline: wgpu::RenderPipeline,
+    layout: wgpu::PipelineLayout,
+    module: wgpu::ShaderModule,
+    depth_compare: wgpu::CompareFunction,
+    indices: wgpu::Buffer,
+    instances: buffer::CpuBuffer<Instance>,
+}
+
+impl PickingPipeline {
+    pub fn new(
+        context: &Context,
+        camera_binder: &CameraBinder,
+        depth_compare: wgpu::CompareFunction,
+    ) -> Self {
+        let instances = buffer::CpuBuffer::with_capacity(context, 16, wgpu::BufferUsages::VERTEX);
+
+        let indices = context
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("PickingPipeline::indices"),
+                contents: bytemuck::cast_slice(&BOX_INDICES),
+                usage: wgpu::BufferUsages::INDEX,
+            });
+
+        let layout = context
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                bind_group_layouts: &[camera_binder.layout()],
+                ..Default::default()
+            });
+
+        let module = context
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("picking.wgsl"),
+                source: wgpu::ShaderSource::Wgsl(
+                    camera::CameraData::with_wgsl(include_str!("picking.wgsl")).into(),
+                ),
+            });
+
+        let pipeline = create_pipeline(context, &layout, &module, depth_compare);
+
+        Self {
+            pipeline,
+            layout,
+            module,
+            depth_compare,
+            indices,
+            instances,
+        }
+    }
+
+    pub fn set_depth_compare(&mut self, context: &Context, depth_compare: wgpu::CompareFunction) {
+        if self.depth_compare == depth_compare {
+            return;
+        }
+        self.depth_compare = depth_compare;
+        self.pipeline = create_pipeline(context, &self.layout, &self.module, depth_compare);
+    }
+
+    #[cfg(feature = "hot-reload")]
+    pub fn reload_shader(&mut self, context: &Context, source: &str) -> anyhow::Result<()> {
+        use pollster::FutureExt;
+
+        context
+            .device
+            .push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let module = context
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(SHADER_PATH),
+                source: wgpu::ShaderSource::Wgsl(camera::CameraData::with_wgsl(source).into()),
+            });
+        let pipeline = create_pipeline(context, &self.layout, &module, self.depth_compare);
+
+        if let Some(e) = context.device.pop_error_scope().block_on() {
+            anyhow::bail!("{e}");
+        }
+
+        self.pipeline = pipeline;
+        self.module = module;
+
+        Ok(())
+    }
+
+    /// Replaces the boxes to draw. `id` must not be [`picking::NO_OBJECT`].
+    pub fn set_boxes(&mut self, context: &Context, boxes: impl IntoIterator<Item = (Aabb, u32)>) {
+        self.instances.clear();
+        let mut batch = self.instances.batch(context);
+        for (aabb, id) in boxes {
+            debug_assert_ne!(id, picking::NO_OBJECT);
+            batch.push(Instance {
+                min: aabb.min,
+                max: aabb.max,
+                id,
+            });
+        }
+    }
+
+    pub(crate) fn draw<'a: 'b, 'b>(
+        &'a self,
+        pass: &'b mut wgpu::RenderPass<'a>,
+        camera_binding: &'a camera::CameraBinding,
+        camera_slot: usize,
+    ) {
+        pass.set_pipeline(&self.pipeline);
+        camera_binding.set(pass, 0, camera_slot);
+        pass.set_index_buffer(self.indices.slice(..), wgpu::IndexFormat::Uint16);
+        pass.set_vertex_buffer(0, self.instances.buffer.slice(..));
+        pass.draw_indexed(0..BOX_INDICES.len() as u32, 0, 0..self.instances.len());
+    }
+}
+
+fn create_pipeline(
+    context: &Context,
+    layout: &wgpu::PipelineLayout,
+    module: &wgpu::ShaderModule,
+    depth_compare: wgpu::CompareFunction,
+) -> wgpu::RenderPipeline {
+    context
+        .device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("PickingPipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module,
+                entry_point: "vs_main",
+                buffers: &[Instance::VERTEX],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: picking::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: Default::default(),
+            fragment: Some(wgpu::FragmentState {
+                module,
+                entry_point: "fs_main",
+                targets: &[
+                    Some(wgpu::ColorTargetState {
+                        format: picking::ID_FORMAT,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    }),
+                    Some(wgpu::ColorTargetState {
+                        format: picking::DEPTH_VALUE_FORMAT,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    }),
+                ],
+            }),
+            multiview: Default::default(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use pollster::FutureExt;
+
+    use super::*;
+    use crate::resources::{
+        camera::{Camera, Projection},
+        picking::{Pick, PickingTarget},
+    };
+
+    const SIZE: u32 = 64;
+    const ID: u32 = 7;
+
+    fn pick(context: &Context, projection: Projection, x: f64, y: f64) -> Pick {
+        let mut camera = Camera::look_at(
+            glam::vec3(0.0, 0.0, 5.0),
+            glam::Vec3::ZERO,
+            SIZE as f32,
+            SIZE as f32,
+            FRAC_PI_2,
+            0.1,
+            100.0,
+        );
+        camera.set_projection(projection);
+
+        let camera_binder = CameraBinder::new(&context.device);
+        let mut camera_binding = camera_binder.bind(&context.device, 1);
+        camera_binding.update(&context.queue, 0, &camera, 0.0);
+        let mut pipeline =
+            PickingPipeline::new(context, &camera_binder, projection.depth_compare());
+        pipeline.set_boxes(
+            context,
+            [(Aabb::new(glam::Vec3::NEG_ONE, glam::Vec3::ONE), ID)],
+        );
+
+        let mut target = PickingTarget::new(context, SIZE, SIZE);
+        target.request(x, y);
+        assert!(target.needs_pass());
+        let mut encoder = context.device.create_command_encoder(&Default::default());
+        {
+            let mut pass = target.begin_pass(context, &mut encoder, &camera);
+            pipeline.draw(&mut pass, &camera_binding, 0);
+        }
+        target.finish(context, encoder, &camera);
+        assert!(!target.needs_pass());
+
+        loop {
+            context.device.poll(wgpu::Maintain::Wait);
+            if let Some(pick) = target.poll(context) {
+                return pick;
+            }
+        }
+    }
+
+    #[test]
+    fn picks_ids_and_positions() {
+        // Needs a GPU, or at least a software adapter. Machines without one
+        // have to opt out explicitly.
+        let context = match Context::headless(SIZE, SIZE).block_on() {
+            Ok(context) => context,
+            Err(e) if std::env::var_os("WGPU_TEMPLATE_SKIP_GPU_TESTS").is_some() => {
+                eprintln!("Skipping, no adapter: {e}");
+                return;
+            }
+            Err(e) => panic!("No adapter, set WGPU_TEMPLATE_SKIP_GPU_TESTS to skip: {e}"),
+        };
+
+        for projection in [
+            Projection::Perspective {
+                fovy: FRAC_PI_2,
+                near: 0.1,
+                far: 100.0,
+            },
+            Projection::ReverseInfinite {
+                fovy: FRAC_PI_2,
+                near: 0.1,
+            },
+        ] {
+            let center = SIZE as f64 / 2.0;
+            let hit = pick(&context, projection, center, center);
+            assert_eq!(hit.id, ID, "{projection:?}");
+            let position = hit.position.unwrap();
+            // The front face, off center by half a pixel.
+            assert!((position.z - 1.0).abs() < 1e-3, "{projection:?} {position}");
+            assert!(
+                position.truncate().length() < 0.2,
+                "{projection:?} {position}"
+            );
+
+            let miss = pick(&context, projection, 0.0, 0.0);
+            assert_eq!(miss.id, picking::NO_OBJECT);
+            assert_eq!(miss.position, None);
+        }
+    }
+}
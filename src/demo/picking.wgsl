@@ -0,0 +1,44 @@
+// CameraData is prepended from camera::CameraData::WGSL.
+
+struct Instance {
+    @location(0)
+    min: vec3<f32>,
+    @location(1)
+    max: vec3<f32>,
+    @location(2)
+    id: u32,
+}
+
+struct VsOut {
+    @builtin(position)
+    frag_position: vec4<f32>,
+    @location(0) @interpolate(flat)
+    id: u32,
+}
+
+@group(0)
+@binding(0)
+var<uniform> camera: CameraData;
+
+// The index buffer picks the 8 corners of the box, one bit per axis.
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, instance: Instance) -> VsOut {
+    let corner = vec3(f32(index & 1u), f32((index >> 1u) & 1u), f32((index >> 2u) & 1u));
+    let position = mix(instance.min, instance.max, corner);
+    let frag_position = camera.view_proj * vec4(position, 1.0);
+
+    return VsOut(frag_position, instance.id);
+}
+
+struct FsOut {
+    @location(0)
+    id: u32,
+    // Depth textures can't always be copied one pixel at a time.
+    @location(1)
+    depth: f32,
+}
+
+@fragment
+fn fs_main(in: VsOut) -> FsOut {
+    return FsOut(in.id, in.frag_position.z);
+}
@@ -0,0 +1,510 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+pub type GamepadId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    Select,
+    Start,
+    Mode,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+/// Sticks go from -1 to 1 with y pointing up, triggers from 0 to 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl GamepadAxis {
+    pub fn is_trigger(self) -> bool {
+        matches!(self, Self::LeftTrigger | Self::RightTrigger)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum GamepadEvent {
+    Connected {
+        id: GamepadId,
+        name: String,
+    },
+    Disconnected {
+        id: GamepadId,
+    },
+    Button {
+        id: GamepadId,
+        button: GamepadButton,
+        pressed: bool,
+    },
+    Axis {
+        id: GamepadId,
+        axis: GamepadAxis,
+        value: f32,
+    },
+}
+
+/// Axis values closer to rest than the dead zone are reported as 0, the
+/// rest of the range is stretched to start at 0 again.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct DeadZones {
+    pub stick: f32,
+    pub trigger: f32,
+}
+
+impl Default for DeadZones {
+    fn default() -> Self {
+        Self {
+            stick: 0.15,
+            trigger: 0.05,
+        }
+    }
+}
+
+impl DeadZones {
+    pub fn apply(&self, axis: GamepadAxis, value: f32) -> f32 {
+        let dead_zone = if axis.is_trigger() {
+            self.trigger
+        } else {
+            self.stick
+        };
+        let magnitude = value.abs();
+        if magnitude <= dead_zone {
+            return 0.0;
+        }
+        value.signum() * ((magnitude - dead_zone) / (1.0 - dead_zone)).min(1.0)
+    }
+}
+
+/// Where gamepad events come from.
+pub trait Backend {
+    fn next_event(&mut self) -> Option<GamepadEvent>;
+}
+
+/// A backend that reports whatever events are pushed into it.
+#[derive(Debug, Default)]
+pub struct MockBackend {
+    events: VecDeque<GamepadEvent>,
+}
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, event: GamepadEvent) -> &mut Self {
+        self.events.push_back(event);
+        self
+    }
+}
+
+impl Backend for MockBackend {
+    fn next_event(&mut self) -> Option<GamepadEvent> {
+        self.events.pop_front()
+    }
+}
+
+/// Polls a [`Backend`], applies the dead zones and keeps track of which
+/// gamepads are connected.
+pub struct Gamepads {
+    backend: Option<Box<dyn Backend>>,
+    dead_zones: DeadZones,
+    focused: bool,
+    connected: BTreeMap<GamepadId, String>,
+    axes: HashMap<(GamepadId, GamepadAxis), f32>,
+}
+
+impl Gamepads {
+    pub fn new(backend: impl Backend + 'static, dead_zones: DeadZones) -> Self {
+        Self {
+            backend: Some(Box::new(backend)),
+            dead_zones,
+            focused: true,
+            connected: BTreeMap::new(),
+            axes: HashMap::new(),
+        }
+    }
+
+    /// Never reports anything.
+    pub fn disabled() -> Self {
+        Self {
+            backend: None,
+            dead_zones: DeadZones::default(),
+            focused: true,
+            connected: BTreeMap::new(),
+            axes: HashMap::new(),
+        }
+    }
+
+    /// Uses gilrs if the `gamepad` feature is enabled.
+    #[cfg(feature = "gamepad")]
+    pub fn standard(dead_zones: DeadZones) -> Self {
+        match gilrs_backend::GilrsBackend::new() {
+            Ok(backend) => Self::new(backend, dead_zones),
+            Err(e) => {
+                log::warn!("Gamepads disabled: {e}");
+                Self::disabled()
+            }
+        }
+    }
+
+    /// Uses gilrs if the `gamepad` feature is enabled.
+    #[cfg(not(feature = "gamepad"))]
+    pub fn standard(_dead_zones: DeadZones) -> Self {
+        Self::disabled()
+    }
+
+    pub fn set_dead_zones(&mut self, dead_zones: DeadZones) {
+        self.dead_zones = dead_zones;
+    }
+
+    /// Like device events, buttons and axes are only reported while the
+    /// window is focused. Losing focus forgets the last reported axis
+    /// values, the same way [`InputMap::release_all`] does, so a stick
+    /// held through a refocus is reported again with its next event.
+    ///
+    /// [`InputMap::release_all`]: super::InputMap::release_all
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+        if !focused {
+            self.axes.clear();
+        }
+    }
+
+    pub fn connected(&self) -> impl Iterator<Item = (GamepadId, &str)> {
+        self.connected.iter().map(|(id, name)| (*id, name.as_str()))
+    }
+
+    /// Returns everything that happened since the last call. Axis events
+    /// that don't change the value after applying the dead zones are
+    /// dropped, so a resting stick stays quiet.
+    pub fn poll(&mut self) -> Vec<GamepadEvent> {
+        let Some(backend) = &mut self.backend else {
+            return Vec::new();
+        };
+
+        let mut events = Vec::new();
+        while let Some(event) = backend.next_event() {
+            match event {
+                GamepadEvent::Connected { id, ref name } => {
+                    self.connected.insert(id, name.clone());
+                }
+                GamepadEvent::Disconnected { id } => {
+                    self.connected.remove(&id);
+                    self.axes.retain(|(pad, _), _| *pad != id);
+                }
+                GamepadEvent::Button { .. } | GamepadEvent::Axis { .. } if !self.focused => {
+                    continue;
+                }
+                GamepadEvent::Button { .. } => {}
+                GamepadEvent::Axis { id, axis, value } => {
+                    let value = self.dead_zones.apply(axis, value);
+                    let old = self.axes.insert((id, axis), value).unwrap_or(0.0);
+                    if old == value {
+                        continue;
+                    }
+                    events.push(GamepadEvent::Axis { id, axis, value });
+                    continue;
+                }
+            }
+            events.push(event);
+        }
+        events
+    }
+}
+
+#[cfg(feature = "gamepad")]
+mod gilrs_backend {
+    use gilrs::{Axis, Button, EventType, Gilrs, GilrsBuilder};
+
+    use super::{Backend, GamepadAxis, GamepadButton, GamepadEvent};
+
+    pub struct GilrsBackend {
+        gilrs: Gilrs,
+    }
+
+    impl GilrsBackend {
+        pub fn new() -> anyhow::Result<Self> {
+            // The default filters include a fixed dead zone, ours is
+            // configurable.
+            let gilrs = GilrsBuilder::new()
+                .with_default_filters(false)
+                .build()
+                .map_err(|e| anyhow::anyhow!("{e}"))?;
+            Ok(Self { gilrs })
+        }
+    }
+
+    impl Backend for GilrsBackend {
+        fn next_event(&mut self) -> Option<GamepadEvent> {
+            while let Some(gilrs::Event { id: pad, event, .. }) = self.gilrs.next_event() {
+                let id = pad.into();
+                let event = match event {
+                    EventType::Connected => GamepadEvent::Connected {
+                        id,
+                        name: self.gilrs.gamepad(pad).name().to_owned(),
+                    },
+                    EventType::Disconnected => GamepadEvent::Disconnected { id },
+                    EventType::ButtonPressed(button, _) | EventType::ButtonReleased(button, _) => {
+                        let Some(button) = map_button(button) else {
+                            continue;
+                        };
+                        let pressed = matches!(event, EventType::ButtonPressed(..));
+                        GamepadEvent::Button {
+                            id,
+                            button,
+                            pressed,
+                        }
+                    }
+                    // Analog triggers are reported as buttons with a value.
+                    EventType::ButtonChanged(Button::LeftTrigger2, value, _) => {
+                        GamepadEvent::Axis {
+                            id,
+                            axis: GamepadAxis::LeftTrigger,
+                            value,
+                        }
+                    }
+                    EventType::ButtonChanged(Button::RightTrigger2, value, _) => {
+                        GamepadEvent::Axis {
+                            id,
+                            axis: GamepadAxis::RightTrigger,
+                            value,
+                        }
+                    }
+                    EventType::AxisChanged(axis, value, _) => {
+                        let Some(axis) = map_axis(axis) else {
+                            continue;
+                        };
+                        GamepadEvent::Axis { id, axis, value }
+                    }
+                    _ => continue,
+                };
+                return Some(event);
+            }
+            None
+        }
+    }
+
+    fn map_button(button: Button) -> Option<GamepadButton> {
+        Some(match button {
+            Button::South => GamepadButton::South,
+            Button::East => GamepadButton::East,
+            Button::North => GamepadButton::North,
+            Button::West => GamepadButton::West,
+            Button::LeftTrigger => GamepadButton::LeftBumper,
+            Button::RightTrigger => GamepadButton::RightBumper,
+            Button::Select => GamepadButton::Select,
+            Button::Start => GamepadButton::Start,
+            Button::Mode => GamepadButton::Mode,
+            Button::LeftThumb => GamepadButton::LeftThumb,
+            Button::RightThumb => GamepadButton::RightThumb,
+            Button::DPadUp => GamepadButton::DPadUp,
+            Button::DPadDown => GamepadButton::DPadDown,
+            Button::DPadLeft => GamepadButton::DPadLeft,
+            Button::DPadRight => GamepadButton::DPadRight,
+            _ => return None,
+        })
+    }
+
+    fn map_axis(axis: Axis) -> Option<GamepadAxis> {
+        Some(match axis {
+            Axis::LeftStickX => GamepadAxis::LeftStickX,
+            Axis::LeftStickY => GamepadAxis::LeftStickY,
+            Axis::RightStickX => GamepadAxis::RightStickX,
+            Axis::RightStickY => GamepadAxis::RightStickY,
+            Axis::LeftZ => GamepadAxis::LeftTrigger,
+            Axis::RightZ => GamepadAxis::RightTrigger,
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+    use crate::input::{self, AxisBinding, Binding, Bindings, Button, InputMap};
+
+    fn gamepads(events: &[GamepadEvent]) -> Gamepads {
+        let mut backend = MockBackend::new();
+        for event in events {
+            backend.push(event.clone());
+        }
+        Gamepads::new(backend, DeadZones::default())
+    }
+
+    impl Backend for Rc<RefCell<MockBackend>> {
+        fn next_event(&mut self) -> Option<GamepadEvent> {
+            self.borrow_mut().next_event()
+        }
+    }
+
+    fn axis(axis: GamepadAxis, value: f32) -> GamepadEvent {
+        GamepadEvent::Axis { id: 0, axis, value }
+    }
+
+    #[test]
+    fn dead_zone_zeroes_small_values() {
+        let dead_zones = DeadZones::default();
+        assert_eq!(dead_zones.apply(GamepadAxis::LeftStickX, 0.1), 0.0);
+        assert_eq!(dead_zones.apply(GamepadAxis::LeftStickX, -0.15), 0.0);
+        assert_eq!(dead_zones.apply(GamepadAxis::LeftTrigger, 0.04), 0.0);
+    }
+
+    #[test]
+    fn dead_zone_rescales_the_rest() {
+        let dead_zones = DeadZones {
+            stick: 0.2,
+            trigger: 0.0,
+        };
+        assert_eq!(dead_zones.apply(GamepadAxis::RightStickY, 1.0), 1.0);
+        assert_eq!(dead_zones.apply(GamepadAxis::RightStickY, -1.0), -1.0);
+        assert!((dead_zones.apply(GamepadAxis::RightStickY, 0.6) - 0.5).abs() < 1e-6);
+        assert_eq!(dead_zones.apply(GamepadAxis::RightTrigger, 0.3), 0.3);
+    }
+
+    #[test]
+    fn poll_drops_unchanged_axis_values() {
+        let mut pads = gamepads(&[
+            axis(GamepadAxis::LeftStickX, 0.05),
+            axis(GamepadAxis::LeftStickX, 1.0),
+            axis(GamepadAxis::LeftStickX, 1.0),
+        ]);
+        assert_eq!(pads.poll(), vec![axis(GamepadAxis::LeftStickX, 1.0)]);
+    }
+
+    #[test]
+    fn refocusing_reports_held_sticks_again() {
+        let backend = Rc::new(RefCell::new(MockBackend::new()));
+        let mut pads = Gamepads::new(backend.clone(), DeadZones::default());
+        backend
+            .borrow_mut()
+            .push(axis(GamepadAxis::LeftStickX, 1.0));
+        assert_eq!(pads.poll(), vec![axis(GamepadAxis::LeftStickX, 1.0)]);
+
+        pads.set_focused(false);
+        backend
+            .borrow_mut()
+            .push(axis(GamepadAxis::LeftStickX, 0.99))
+            .push(GamepadEvent::Button {
+                id: 0,
+                button: GamepadButton::South,
+                pressed: true,
+            })
+            .push(GamepadEvent::Disconnected { id: 1 });
+        assert_eq!(pads.poll(), vec![GamepadEvent::Disconnected { id: 1 }]);
+
+        // Still fully deflected, which is the same value after the dead zone.
+        pads.set_focused(true);
+        backend
+            .borrow_mut()
+            .push(axis(GamepadAxis::LeftStickX, 1.0));
+        assert_eq!(pads.poll(), vec![axis(GamepadAxis::LeftStickX, 1.0)]);
+    }
+
+    #[test]
+    fn tracks_connected_gamepads() {
+        let mut pads = gamepads(&[
+            GamepadEvent::Connected {
+                id: 0,
+                name: "Pad A".to_owned(),
+            },
+            GamepadEvent::Connected {
+                id: 1,
+                name: "Pad B".to_owned(),
+            },
+            GamepadEvent::Disconnected { id: 0 },
+        ]);
+        assert_eq!(pads.poll().len(), 3);
+        assert_eq!(pads.connected().collect::<Vec<_>>(), vec![(1, "Pad B")]);
+    }
+
+    #[test]
+    fn disabled_reports_nothing() {
+        assert!(Gamepads::disabled().poll().is_empty());
+    }
+
+    #[test]
+    fn buttons_trigger_actions() {
+        let mut bindings = Bindings::default();
+        bindings.bind(
+            "jump",
+            Binding::button(Button::Gamepad(GamepadButton::South)),
+        );
+        let mut input = InputMap::new(bindings);
+
+        let mut pads = gamepads(&[GamepadEvent::Button {
+            id: 0,
+            button: GamepadButton::South,
+            pressed: true,
+        }]);
+        for event in pads.poll() {
+            input.on_gamepad(&event);
+        }
+        assert!(input.just_pressed("jump"));
+        assert!(input.pressed("jump"));
+
+        input.end_frame();
+        input.on_gamepad(&GamepadEvent::Disconnected { id: 0 });
+        assert!(!input.pressed("jump"));
+        assert!(input.just_released("jump"));
+    }
+
+    #[test]
+    fn button_held_on_two_pads_stays_held() {
+        let mut bindings = Bindings::default();
+        bindings.bind(
+            "jump",
+            Binding::button(Button::Gamepad(GamepadButton::South)),
+        );
+        let mut input = InputMap::new(bindings);
+
+        for id in [0, 1] {
+            input.on_gamepad(&GamepadEvent::Button {
+                id,
+                button: GamepadButton::South,
+                pressed: true,
+            });
+        }
+        input.on_gamepad(&GamepadEvent::Button {
+            id: 0,
+            button: GamepadButton::South,
+            pressed: false,
+        });
+        assert!(input.pressed("jump"));
+    }
+
+    #[test]
+    fn sticks_drive_axes() {
+        let mut input = InputMap::new(Bindings::default());
+        input.on_gamepad(&axis(GamepadAxis::RightStickX, 0.5));
+
+        let scale = Bindings::default().axes[input::TURN_X]
+            .iter()
+            .find(|binding| binding.axis == input::Axis::Gamepad(GamepadAxis::RightStickX))
+            .map(|binding: &AxisBinding| binding.scale)
+            .unwrap();
+        assert_eq!(input.value(input::TURN_X), 0.5 * scale);
+
+        // Stick positions are state, unlike mouse motion.
+        input.end_frame();
+        assert_eq!(input.value(input::TURN_X), 0.5 * scale);
+    }
+}
@@ -0,0 +1,633 @@
+pub mod gamepad;
+pub mod record;
+pub mod touch;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use gamepad::{GamepadAxis, GamepadButton, GamepadEvent, GamepadId};
+use winit::{event::MouseButton, keyboard::KeyCode, keyboard::ModifiersState};
+
+pub const TOGGLE_FULLSCREEN: &str = "toggle_fullscreen";
+pub const NEXT_MONITOR: &str = "next_monitor";
+pub const QUIT: &str = "quit";
+pub const TOGGLE_MOUSE_LOOK: &str = "toggle_mouse_look";
+pub const LOOK_X: &str = "look_x";
+pub const LOOK_Y: &str = "look_y";
+/// Like `look_*` but a rate in radians per second instead of a distance.
+pub const TURN_X: &str = "turn_x";
+pub const TURN_Y: &str = "turn_y";
+pub const MOVE_FORWARD: &str = "move_forward";
+pub const MOVE_RIGHT: &str = "move_right";
+pub const MOVE_UP: &str = "move_up";
+pub const MOVE_FAST: &str = "move_fast";
+pub const SWITCH_CAMERA: &str = "switch_camera";
+pub const ORBIT_ROTATE: &str = "orbit_rotate";
+pub const ORBIT_PAN: &str = "orbit_pan";
+pub const ORBIT_ZOOM: &str = "orbit_zoom";
+pub const FRAME_SCENE: &str = "frame_scene";
+pub const CYCLE_PROJECTION: &str = "cycle_projection";
+pub const PLACE_MARKER: &str = "place_marker";
+pub const PICK: &str = "pick";
+pub const CYCLE_LAYOUT: &str = "cycle_layout";
+pub const RECORD_PATH: &str = "record_path";
+pub const CAPTURE_KEYFRAME: &str = "capture_keyframe";
+pub const PLAY_PATH: &str = "play_path";
+
+/// Something that is either held or not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+pub enum Button {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// The button on any connected gamepad.
+    Gamepad(GamepadButton),
+}
+
+/// Something that produces a value each frame.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum Axis {
+    /// Raw mouse motion, axis 0 is x and 1 is y on most platforms.
+    MouseMotion(u32),
+    ScrollX,
+    ScrollY,
+    /// -1, 0 or 1 depending on which of the two buttons are held.
+    Buttons {
+        negative: Button,
+        positive: Button,
+    },
+    /// The sum over all connected gamepads, clamped to -1..1.
+    Gamepad(GamepadAxis),
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub logo: bool,
+}
+
+impl From<ModifiersState> for Modifiers {
+    fn from(state: ModifiersState) -> Self {
+        Self {
+            shift: state.shift_key(),
+            ctrl: state.control_key(),
+            alt: state.alt_key(),
+            logo: state.super_key(),
+        }
+    }
+}
+
+/// Triggers an action when `button` is pressed while everything in `with`
+/// is held. If `modifiers` is set they have to match exactly, otherwise
+/// they are ignored.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Binding {
+    pub button: Button,
+    #[serde(default)]
+    pub with: Vec<Button>,
+    #[serde(default)]
+    pub modifiers: Option<Modifiers>,
+}
+
+impl Binding {
+    pub fn key(key: KeyCode) -> Self {
+        Self::button(Button::Key(key))
+    }
+
+    pub fn button(button: Button) -> Self {
+        Self {
+            button,
+            with: Vec::new(),
+            modifiers: None,
+        }
+    }
+
+    pub fn with_modifiers(mut self, modifiers: Modifiers) -> Self {
+        self.modifiers = Some(modifiers);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct AxisBinding {
+    pub axis: Axis,
+    #[serde(default = "one")]
+    pub scale: f32,
+}
+
+impl AxisBinding {
+    pub fn new(axis: Axis, scale: f32) -> Self {
+        Self { axis, scale }
+    }
+}
+
+fn one() -> f32 {
+    1.0
+}
+
+/// Named actions and axes and what triggers them.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Bindings {
+    pub actions: BTreeMap<String, Vec<Binding>>,
+    pub axes: BTreeMap<String, Vec<AxisBinding>>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        let alt = Modifiers {
+            alt: true,
+            ..Default::default()
+        };
+        let shift = Modifiers {
+            shift: true,
+            ..Default::default()
+        };
+        let ctrl = Modifiers {
+            ctrl: true,
+            ..Default::default()
+        };
+        let look_sensitivity = 0.002;
+        let turn_speed = 2.0;
+        let keys = |negative, positive| Axis::Buttons {
+            negative: Button::Key(negative),
+            positive: Button::Key(positive),
+        };
+
+        let mut bindings = Self {
+            actions: BTreeMap::new(),
+            axes: BTreeMap::new(),
+        };
+        bindings
+            .bind(
+                TOGGLE_FULLSCREEN,
+                Binding::key(KeyCode::F11).with_modifiers(Modifiers::default()),
+            )
+            .bind(
+                TOGGLE_FULLSCREEN,
+                Binding::key(KeyCode::Enter).with_modifiers(alt),
+            )
+            .bind(
+                NEXT_MONITOR,
+                Binding::key(KeyCode::F11).with_modifiers(shift),
+            )
+            .bind(QUIT, Binding::key(KeyCode::Escape))
+            .bind(TOGGLE_MOUSE_LOOK, Binding::key(KeyCode::Tab))
+            .bind(MOVE_FAST, Binding::key(KeyCode::ShiftLeft))
+            .bind(
+                MOVE_FAST,
+                Binding::button(Button::Gamepad(GamepadButton::LeftThumb)),
+            )
+            .bind(SWITCH_CAMERA, Binding::key(KeyCode::KeyC))
+            .bind(FRAME_SCENE, Binding::key(KeyCode::KeyF))
+            .bind(CYCLE_PROJECTION, Binding::key(KeyCode::KeyP))
+            .bind(CYCLE_LAYOUT, Binding::key(KeyCode::KeyL))
+            .bind(RECORD_PATH, Binding::key(KeyCode::KeyR))
+            .bind(CAPTURE_KEYFRAME, Binding::key(KeyCode::KeyK))
+            .bind(PLAY_PATH, Binding::key(KeyCode::KeyT))
+            .bind(
+                PLACE_MARKER,
+                Binding::button(Button::Mouse(MouseButton::Right)),
+            )
+            .bind(
+                PICK,
+                Binding::button(Button::Mouse(MouseButton::Left)).with_modifiers(ctrl),
+            )
+            // Without modifiers, so a Ctrl-click only picks.
+            .bind(
+                ORBIT_ROTATE,
+                Binding::button(Button::Mouse(MouseButton::Left))
+                    .with_modifiers(Modifiers::default()),
+            )
+            .bind(
+                ORBIT_PAN,
+                Binding::button(Button::Mouse(MouseButton::Middle)),
+            )
+            .bind_axis(ORBIT_ZOOM, AxisBinding::new(Axis::ScrollY, 1.0))
+            .bind_axis(
+                LOOK_X,
+                AxisBinding::new(Axis::MouseMotion(0), look_sensitivity),
+            )
+            .bind_axis(
+                LOOK_Y,
+                AxisBinding::new(Axis::MouseMotion(1), -look_sensitivity),
+            )
+            .bind_axis(
+                TURN_X,
+                AxisBinding::new(Axis::Gamepad(GamepadAxis::RightStickX), turn_speed),
+            )
+            .bind_axis(
+                TURN_Y,
+                AxisBinding::new(Axis::Gamepad(GamepadAxis::RightStickY), turn_speed),
+            )
+            .bind_axis(
+                MOVE_FORWARD,
+                AxisBinding::new(keys(KeyCode::KeyS, KeyCode::KeyW), 1.0),
+            )
+            .bind_axis(
+                MOVE_FORWARD,
+                AxisBinding::new(Axis::Gamepad(GamepadAxis::LeftStickY), 1.0),
+            )
+            .bind_axis(
+                MOVE_RIGHT,
+                AxisBinding::new(keys(KeyCode::KeyA, KeyCode::KeyD), 1.0),
+            )
+            .bind_axis(
+                MOVE_RIGHT,
+                AxisBinding::new(Axis::Gamepad(GamepadAxis::LeftStickX), 1.0),
+            )
+            .bind_axis(
+                MOVE_UP,
+                AxisBinding::new(keys(KeyCode::KeyQ, KeyCode::KeyE), 1.0),
+            )
+            .bind_axis(
+                MOVE_UP,
+                AxisBinding::new(
+                    Axis::Buttons {
+                        negative: Button::Gamepad(GamepadButton::LeftBumper),
+                        positive: Button::Gamepad(GamepadButton::RightBumper),
+                    },
+                    1.0,
+                ),
+            );
+        bindings
+    }
+}
+
+impl Bindings {
+    pub fn bind(&mut self, action: &str, binding: Binding) -> &mut Self {
+        self.actions
+            .entry(action.to_owned())
+            .or_default()
+            .push(binding);
+        self
+    }
+
+    pub fn bind_axis(&mut self, axis: &str, binding: AxisBinding) -> &mut Self {
+        self.axes.entry(axis.to_owned()).or_default().push(binding);
+        self
+    }
+}
+
+/// Tracks the state of every [`Button`] and [`Axis`] and answers questions
+/// about the named actions and axes in its [`Bindings`].
+///
+/// Feed it events as they arrive and call [`InputMap::end_frame`] once the
+/// frame has been updated.
+#[derive(Debug, Default)]
+pub struct InputMap {
+    bindings: Bindings,
+    held: HashSet<Button>,
+    just_pressed: HashSet<Button>,
+    just_released: HashSet<Button>,
+    modifiers: Modifiers,
+    motion: HashMap<u32, f32>,
+    scroll: (f32, f32),
+    gamepad_buttons: HashSet<(GamepadId, GamepadButton)>,
+    gamepad_axes: HashMap<(GamepadId, GamepadAxis), f32>,
+}
+
+impl InputMap {
+    pub fn new(bindings: Bindings) -> Self {
+        Self {
+            bindings,
+            ..Default::default()
+        }
+    }
+
+    pub fn bindings(&self) -> &Bindings {
+        &self.bindings
+    }
+
+    pub fn set_bindings(&mut self, bindings: Bindings) {
+        self.bindings = bindings;
+    }
+
+    pub fn on_button(&mut self, button: Button, pressed: bool) {
+        if pressed {
+            if self.held.insert(button) {
+                self.just_pressed.insert(button);
+            }
+        } else if self.held.remove(&button) {
+            self.just_released.insert(button);
+        }
+    }
+
+    pub fn on_modifiers(&mut self, modifiers: impl Into<Modifiers>) {
+        self.modifiers = modifiers.into();
+    }
+
+    pub fn on_motion(&mut self, axis: u32, value: f64) {
+        *self.motion.entry(axis).or_default() += value as f32;
+    }
+
+    pub fn on_scroll(&mut self, x: f32, y: f32) {
+        self.scroll.0 += x;
+        self.scroll.1 += y;
+    }
+
+    pub fn on_gamepad(&mut self, event: &GamepadEvent) {
+        match *event {
+            GamepadEvent::Connected { .. } => {}
+            GamepadEvent::Disconnected { id } => {
+                let released: Vec<_> = self
+                    .gamepad_buttons
+                    .iter()
+                    .filter(|(pad, _)| *pad == id)
+                    .copied()
+                    .collect();
+                for (_, button) in released {
+                    self.on_gamepad_button(id, button, false);
+                }
+                self.gamepad_axes.retain(|(pad, _), _| *pad != id);
+            }
+            GamepadEvent::Button {
+                id,
+                button,
+                pressed,
+            } => self.on_gamepad_button(id, button, pressed),
+            GamepadEvent::Axis { id, axis, value } => {
+                self.gamepad_axes.insert((id, axis), value);
+            }
+        }
+    }
+
+    /// Releases everything, for when the window loses focus and release
+    /// events would go missing.
+    pub fn release_all(&mut self) {
+        self.just_released.extend(self.held.drain());
+        self.gamepad_buttons.clear();
+        self.gamepad_axes.clear();
+    }
+
+    /// Clears the per frame state, motion and scroll accumulate until then.
+    pub fn end_frame(&mut self) {
+        self.just_pressed.clear();
+        self.just_released.clear();
+        self.motion.clear();
+        self.scroll = (0.0, 0.0);
+    }
+
+    pub fn is_held(&self, button: Button) -> bool {
+        self.held.contains(&button)
+    }
+
+    pub fn pressed(&self, action: &str) -> bool {
+        self.bindings_for(action)
+            .any(|b| self.is_held(b.button) && self.chord_held(b))
+    }
+
+    pub fn just_pressed(&self, action: &str) -> bool {
+        self.bindings_for(action)
+            .any(|b| self.just_pressed.contains(&b.button) && self.chord_held(b))
+    }
+
+    pub fn just_released(&self, action: &str) -> bool {
+        !self.pressed(action)
+            && self
+                .bindings_for(action)
+                .any(|b| self.just_released.contains(&b.button))
+    }
+
+    /// The sum of every binding for `axis` this frame.
+    pub fn value(&self, axis: &str) -> f32 {
+        let Some(bindings) = self.bindings.axes.get(axis) else {
+            return 0.0;
+        };
+
+        bindings
+            .iter()
+            .map(|binding| {
+                let value = match binding.axis {
+                    Axis::MouseMotion(axis) => self.motion.get(&axis).copied().unwrap_or(0.0),
+                    Axis::ScrollX => self.scroll.0,
+                    Axis::ScrollY => self.scroll.1,
+                    Axis::Buttons { negative, positive } => {
+                        self.is_held(positive) as i32 as f32 - self.is_held(negative) as i32 as f32
+                    }
+                    Axis::Gamepad(axis) => self
+                        .gamepad_axes
+                        .iter()
+                        .filter(|((_, a), _)| *a == axis)
+                        .map(|(_, value)| value)
+                        .sum::<f32>()
+                        .clamp(-1.0, 1.0),
+                };
+                value * binding.scale
+            })
+            .sum()
+    }
+
+    /// A gamepad button counts as held while any gamepad holds it.
+    fn on_gamepad_button(&mut self, id: GamepadId, button: GamepadButton, pressed: bool) {
+        if pressed {
+            self.gamepad_buttons.insert((id, button));
+        } else {
+            self.gamepad_buttons.remove(&(id, button));
+        }
+        let held = self.gamepad_buttons.iter().any(|(_, b)| *b == button);
+        self.on_button(Button::Gamepad(button), held);
+    }
+
+    fn bindings_for(&self, action: &str) -> impl Iterator<Item = &Binding> {
+        self.bindings.actions.get(action).into_iter().flatten()
+    }
+
+    fn chord_held(&self, binding: &Binding) -> bool {
+        binding.with.iter().all(|b| self.is_held(*b))
+            && binding.modifiers.is_none_or(|m| m == self.modifiers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JUMP: &str = "jump";
+    const SAVE: &str = "save";
+    const MOVE: &str = "move";
+
+    fn key(key: KeyCode) -> Button {
+        Button::Key(key)
+    }
+
+    fn input() -> InputMap {
+        let ctrl = Modifiers {
+            ctrl: true,
+            ..Default::default()
+        };
+        let mut bindings = Bindings {
+            actions: BTreeMap::new(),
+            axes: BTreeMap::new(),
+        };
+        bindings
+            .bind(JUMP, Binding::key(KeyCode::Space))
+            .bind(JUMP, Binding::button(Button::Mouse(MouseButton::Left)))
+            .bind(SAVE, Binding::key(KeyCode::KeyS).with_modifiers(ctrl))
+            .bind(
+                SAVE,
+                Binding {
+                    button: key(KeyCode::KeyW),
+                    with: vec![key(KeyCode::KeyQ)],
+                    modifiers: None,
+                },
+            )
+            .bind_axis(
+                MOVE,
+                AxisBinding::new(
+                    Axis::Buttons {
+                        negative: key(KeyCode::KeyA),
+                        positive: key(KeyCode::KeyD),
+                    },
+                    1.0,
+                ),
+            )
+            .bind_axis(MOVE, AxisBinding::new(Axis::MouseMotion(0), 0.5))
+            .bind_axis(MOVE, AxisBinding::new(Axis::ScrollY, -2.0));
+        InputMap::new(bindings)
+    }
+
+    #[test]
+    fn presses_and_releases_last_one_frame() {
+        let mut input = input();
+        input.on_button(key(KeyCode::Space), true);
+        assert!(input.pressed(JUMP));
+        assert!(input.just_pressed(JUMP));
+
+        input.end_frame();
+        // Key repeat doesn't press again.
+        input.on_button(key(KeyCode::Space), true);
+        assert!(input.pressed(JUMP));
+        assert!(!input.just_pressed(JUMP));
+
+        input.on_button(key(KeyCode::Space), false);
+        assert!(!input.pressed(JUMP));
+        assert!(input.just_released(JUMP));
+        input.end_frame();
+        assert!(!input.just_released(JUMP));
+    }
+
+    #[test]
+    fn releasing_one_binding_keeps_the_action_held() {
+        let mut input = input();
+        input.on_button(key(KeyCode::Space), true);
+        input.on_button(Button::Mouse(MouseButton::Left), true);
+        input.end_frame();
+
+        input.on_button(key(KeyCode::Space), false);
+        assert!(input.pressed(JUMP));
+        assert!(!input.just_released(JUMP));
+
+        input.release_all();
+        assert!(!input.pressed(JUMP));
+        assert!(input.just_released(JUMP));
+    }
+
+    #[test]
+    fn modifiers_must_match_exactly() {
+        let mut input = input();
+        input.on_button(key(KeyCode::KeyS), true);
+        assert!(!input.just_pressed(SAVE));
+        input.on_button(key(KeyCode::KeyS), false);
+        input.end_frame();
+
+        input.on_modifiers(Modifiers {
+            ctrl: true,
+            shift: true,
+            ..Default::default()
+        });
+        input.on_button(key(KeyCode::KeyS), true);
+        assert!(!input.just_pressed(SAVE));
+        input.on_button(key(KeyCode::KeyS), false);
+        input.end_frame();
+
+        input.on_modifiers(ModifiersState::CONTROL);
+        input.on_button(key(KeyCode::KeyS), true);
+        assert!(input.just_pressed(SAVE));
+    }
+
+    #[test]
+    fn ctrl_click_only_picks() {
+        let mut input = InputMap::new(Bindings::default());
+        let left = Button::Mouse(MouseButton::Left);
+        input.on_modifiers(ModifiersState::CONTROL);
+        input.on_button(left, true);
+        assert!(input.just_pressed(PICK));
+        assert!(!input.pressed(ORBIT_ROTATE));
+        input.on_button(left, false);
+        input.end_frame();
+
+        input.on_modifiers(ModifiersState::empty());
+        input.on_button(left, true);
+        assert!(!input.just_pressed(PICK));
+        assert!(input.just_pressed(ORBIT_ROTATE));
+    }
+
+    #[test]
+    fn chords_need_every_button() {
+        let mut input = input();
+        input.on_button(key(KeyCode::KeyW), true);
+        assert!(!input.pressed(SAVE));
+        input.on_button(key(KeyCode::KeyW), false);
+        input.end_frame();
+
+        input.on_button(key(KeyCode::KeyQ), true);
+        input.on_button(key(KeyCode::KeyW), true);
+        assert!(input.just_pressed(SAVE));
+        // Any modifiers are fine without `modifiers`.
+        input.on_modifiers(ModifiersState::SHIFT);
+        assert!(input.pressed(SAVE));
+        input.on_button(key(KeyCode::KeyQ), false);
+        assert!(!input.pressed(SAVE));
+    }
+
+    #[test]
+    fn axis_bindings_are_summed() {
+        let mut input = input();
+        assert_eq!(input.value(MOVE), 0.0);
+        assert_eq!(input.value("unbound"), 0.0);
+
+        input.on_button(key(KeyCode::KeyD), true);
+        assert_eq!(input.value(MOVE), 1.0);
+        input.on_button(key(KeyCode::KeyA), true);
+        assert_eq!(input.value(MOVE), 0.0);
+        input.on_button(key(KeyCode::KeyA), false);
+
+        // Motion and scroll accumulate over the frame.
+        input.on_motion(0, 3.0);
+        input.on_motion(0, 1.0);
+        input.on_motion(1, 100.0);
+        input.on_scroll(0.0, 0.25);
+        assert_eq!(input.value(MOVE), 1.0 + 4.0 * 0.5 - 0.5);
+
+        input.end_frame();
+        assert_eq!(input.value(MOVE), 1.0);
+    }
+
+    #[test]
+    fn rebinding_applies_immediately() {
+        let mut input = input();
+        input.on_button(key(KeyCode::Enter), true);
+        assert!(!input.pressed(JUMP));
+
+        let mut bindings = input.bindings().clone();
+        bindings.actions.remove(JUMP);
+        bindings.bind(JUMP, Binding::key(KeyCode::Enter));
+        input.set_bindings(bindings);
+        assert!(input.pressed(JUMP));
+        assert!(input.just_pressed(JUMP));
+
+        input.on_button(key(KeyCode::Space), true);
+        input.on_button(key(KeyCode::Enter), false);
+        assert!(!input.pressed(JUMP));
+    }
+
+    #[test]
+    fn bindings_round_trip_through_json() {
+        let bindings = Bindings::default();
+        let json = serde_json::to_string(&bindings).unwrap();
+        let loaded: Bindings = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, bindings);
+    }
+}
@@ -0,0 +1,309 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use pollster::FutureExt;
+
+use winit::keyboard::PhysicalKey;
+
+use crate::{
+    resources::fs::{load_serialized, save_serialized},
+    window::CursorMode,
+};
+
+use super::{gamepad::GamepadEvent, touch::Gesture, Bindings, Button, Modifiers};
+
+pub const RECORDING_VERSION: u32 = 1;
+
+/// Everything `lib::run` forwards to the [`super::InputMap`] and the demo,
+/// after sensitivities and dead zones have been applied.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum InputEvent {
+    /// Physical size of the surface.
+    Resized {
+        width: u32,
+        height: u32,
+    },
+    ScaleFactorChanged {
+        scale_factor: f64,
+    },
+    Focused(bool),
+    Bindings(Bindings),
+    Modifiers(Modifiers),
+    Button {
+        button: Button,
+        pressed: bool,
+    },
+    CursorMoved {
+        x: f64,
+        y: f64,
+    },
+    CursorEntered,
+    CursorLeft,
+    Gesture(Gesture),
+    Scroll {
+        x: f32,
+        y: f32,
+    },
+    Motion {
+        axis: u32,
+        value: f64,
+    },
+    DeviceButton {
+        button: u32,
+        pressed: bool,
+    },
+    DeviceKey {
+        key: PhysicalKey,
+        pressed: bool,
+    },
+    Gamepad(GamepadEvent),
+    /// The cursor mode the window applied, which may differ from the
+    /// requested one.
+    CursorMode(CursorMode),
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct RecordedEvent {
+    /// Events with frame `n` are handled before the `n`th update.
+    pub frame: u64,
+    pub event: InputEvent,
+}
+
+/// A recorded session. The format follows the extension, see
+/// [`crate::resources::fs::Format`]. Bindings are recorded, the rest of the
+/// config, like camera speeds, comes from the config used for the replay.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct Recording {
+    pub version: u32,
+    /// Seconds passed to each update, indexed by frame.
+    pub frame_times: Vec<f32>,
+    pub events: Vec<RecordedEvent>,
+}
+
+impl Recording {
+    pub async fn load(path: &str) -> anyhow::Result<Self> {
+        let recording: Self = load_serialized(path).await?;
+        if recording.version != RECORDING_VERSION {
+            anyhow::bail!(
+                "{path}: recording version {} isn't supported, expected {RECORDING_VERSION}",
+                recording.version
+            );
+        }
+        Ok(recording)
+    }
+
+    pub async fn save(&self, path: &str) -> anyhow::Result<()> {
+        save_serialized(path, self).await
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        self.frame_times.len() as u64
+    }
+}
+
+pub struct Recorder {
+    // Shared with the panic hook, see `save_on_panic`.
+    recording: Arc<Mutex<Recording>>,
+}
+
+impl Recorder {
+    /// The size, scale factor and `bindings` are recorded as the first
+    /// events so the replay starts out the same.
+    pub fn new(width: u32, height: u32, scale_factor: f64, bindings: Bindings) -> Self {
+        let mut recorder = Self {
+            recording: Arc::new(Mutex::new(Recording {
+                version: RECORDING_VERSION,
+                frame_times: Vec::new(),
+                events: Vec::new(),
+            })),
+        };
+        recorder.record(InputEvent::Resized { width, height });
+        recorder.record(InputEvent::ScaleFactorChanged { scale_factor });
+        recorder.record(InputEvent::Bindings(bindings));
+        recorder
+    }
+
+    /// Saves everything recorded so far to `path` if anything panics
+    /// before [`Recorder::finish`], so the session that led up to the panic
+    /// can be replayed. The previous hook runs first.
+    pub fn save_on_panic(&self, path: String) {
+        let recording = Arc::downgrade(&self.recording);
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            previous(info);
+            let Some(recording) = recording.upgrade() else {
+                return;
+            };
+            // Still locked if the panic happened while recording.
+            let Ok(recording) = recording.try_lock() else {
+                log::error!("Unable to save the recording to {path}");
+                return;
+            };
+            match recording.save(&path).block_on() {
+                Ok(()) => log::info!("Saved {} frames to {path}", recording.frame_count()),
+                Err(e) => log::error!("Unable to save the recording to {path}: {e}"),
+            }
+        }));
+    }
+
+    pub fn frame(&self) -> u64 {
+        self.lock().frame_count()
+    }
+
+    pub fn record(&mut self, event: InputEvent) {
+        let mut recording = self.lock();
+        let frame = recording.frame_count();
+        recording.events.push(RecordedEvent { frame, event });
+    }
+
+    pub fn end_frame(&mut self, dt: f32) {
+        self.lock().frame_times.push(dt);
+    }
+
+    pub fn finish(self) -> Recording {
+        match Arc::try_unwrap(self.recording) {
+            Ok(recording) => recording
+                .into_inner()
+                .unwrap_or_else(PoisonError::into_inner),
+            // The panic hook is saving it right now.
+            Err(recording) => recording
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Recording> {
+        self.recording
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Hands out the events of a [`Recording`] one frame at a time.
+pub struct Player {
+    frame_times: Vec<f32>,
+    events: VecDeque<RecordedEvent>,
+    frame: u64,
+}
+
+impl Player {
+    pub fn new(recording: Recording) -> Self {
+        Self {
+            frame_times: recording.frame_times,
+            events: recording.events.into(),
+            frame: 0,
+        }
+    }
+
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.frame_times.len() as u64
+    }
+
+    /// The recorded frame time and the events of the next frame, or `None`
+    /// once the recording is over.
+    pub fn next_frame(&mut self) -> Option<(f32, Vec<InputEvent>)> {
+        let dt = *self.frame_times.get(self.frame as usize)?;
+        let mut events = Vec::new();
+        while let Some(recorded) = self.events.front() {
+            if recorded.frame > self.frame {
+                break;
+            }
+            events.extend(self.events.pop_front().map(|recorded| recorded.event));
+        }
+        self.frame += 1;
+        Some((dt, events))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scroll(y: f32) -> InputEvent {
+        InputEvent::Scroll { x: 0.0, y }
+    }
+
+    fn recorder() -> Recorder {
+        let mut recorder = Recorder::new(800, 600, 1.5, Bindings::default());
+        recorder.record(scroll(1.0));
+        recorder.end_frame(0.1);
+        recorder.end_frame(0.2);
+        recorder.record(scroll(2.0));
+        recorder.record(scroll(3.0));
+        recorder.end_frame(0.3);
+        recorder
+    }
+
+    #[test]
+    fn events_belong_to_the_next_update() {
+        let recorder = recorder();
+        assert_eq!(recorder.frame(), 3);
+
+        let recording = recorder.finish();
+        assert_eq!(recording.version, RECORDING_VERSION);
+        assert_eq!(recording.frame_times, [0.1, 0.2, 0.3]);
+        let frames: Vec<_> = recording.events.iter().map(|event| event.frame).collect();
+        assert_eq!(frames, [0, 0, 0, 0, 2, 2]);
+        assert_eq!(
+            recording.events[0].event,
+            InputEvent::Resized {
+                width: 800,
+                height: 600
+            }
+        );
+    }
+
+    #[test]
+    fn players_replay_what_was_recorded() {
+        let mut player = Player::new(recorder().finish());
+        assert!(!player.is_finished());
+
+        let (dt, events) = player.next_frame().unwrap();
+        assert_eq!(dt, 0.1);
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[3], scroll(1.0));
+        assert_eq!(player.next_frame(), Some((0.2, Vec::new())));
+        assert_eq!(
+            player.next_frame(),
+            Some((0.3, vec![scroll(2.0), scroll(3.0)]))
+        );
+
+        assert!(player.is_finished());
+        assert_eq!(player.frame(), 3);
+        assert_eq!(player.next_frame(), None);
+    }
+
+    #[test]
+    fn recordings_round_trip_through_every_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut recorder = recorder();
+        recorder.record(InputEvent::CursorMode(CursorMode::Confined));
+        let recording = recorder.finish();
+        for extension in ["json", "ron", "bin"] {
+            let path = dir.path().join(format!("input.{extension}"));
+            let path = path.to_str().unwrap();
+            recording.save(path).block_on().unwrap();
+            let loaded = Recording::load(path).block_on().unwrap();
+            assert_eq!(loaded.frame_times, recording.frame_times);
+            assert_eq!(loaded.events, recording.events);
+        }
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("input.json");
+        let path = path.to_str().unwrap();
+        let mut recording = recorder().finish();
+        recording.version = RECORDING_VERSION + 1;
+        recording.save(path).block_on().unwrap();
+        assert!(Recording::load(path).block_on().is_err());
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::BTreeMap;
+
+use winit::event::{Touch, TouchPhase};
+
+/// High level gestures recognized from touch screens and touchpads.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum Gesture {
+    /// Movement of the center of all touches in physical pixels.
+    Pan { dx: f32, dy: f32 },
+    /// Relative change in size, greater than 1 when zooming in.
+    Pinch { scale: f32 },
+    /// Change in angle in radians, counterclockwise.
+    Rotate { radians: f32 },
+}
+
+/// Turns raw [`Touch`] events into [`Gesture`]s.
+///
+/// One finger pans. With two or more fingers the first two pan, pinch and
+/// rotate at the same time, the others are ignored.
+#[derive(Debug, Default)]
+pub struct TouchTracker {
+    touches: BTreeMap<u64, (f32, f32)>,
+}
+
+impl TouchTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn touch_count(&self) -> usize {
+        self.touches.len()
+    }
+
+    pub fn on_touch(&mut self, touch: Touch) -> Vec<Gesture> {
+        let position = (touch.location.x as f32, touch.location.y as f32);
+        match touch.phase {
+            TouchPhase::Started => {
+                self.touches.insert(touch.id, position);
+                Vec::new()
+            }
+            TouchPhase::Moved => {
+                let before = self.pair();
+                let Some(old) = self.touches.insert(touch.id, position) else {
+                    return Vec::new();
+                };
+                match (before, self.pair()) {
+                    (Some(before), Some(after)) => gestures_between(before, after),
+                    _ if self.touches.len() == 1 => vec![Gesture::Pan {
+                        dx: position.0 - old.0,
+                        dy: position.1 - old.1,
+                    }],
+                    _ => Vec::new(),
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.touches.remove(&touch.id);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Forgets every touch, for when the window loses focus and the end
+    /// events would go missing.
+    pub fn clear(&mut self) {
+        self.touches.clear();
+    }
+
+    fn pair(&self) -> Option<((f32, f32), (f32, f32))> {
+        let mut touches = self.touches.values();
+        Some((*touches.next()?, *touches.next()?))
+    }
+}
+
+/// `TouchpadMagnify` reports the change in scale minus one.
+pub fn touchpad_pinch(delta: f64) -> Gesture {
+    Gesture::Pinch {
+        scale: 1.0 + delta as f32,
+    }
+}
+
+/// `TouchpadRotate` reports the change in degrees, counterclockwise.
+pub fn touchpad_rotate(delta: f32) -> Gesture {
+    Gesture::Rotate {
+        radians: delta.to_radians(),
+    }
+}
+
+fn gestures_between(
+    (a0, b0): ((f32, f32), (f32, f32)),
+    (a1, b1): ((f32, f32), (f32, f32)),
+) -> Vec<Gesture> {
+    let mut gestures = Vec::new();
+
+    let center = |a: (f32, f32), b: (f32, f32)| ((a.0 + b.0) * 0.5, (a.1 + b.1) * 0.5);
+    let (c0, c1) = (center(a0, b0), center(a1, b1));
+    let (dx, dy) = (c1.0 - c0.0, c1.1 - c0.1);
+    if dx != 0.0 || dy != 0.0 {
+        gestures.push(Gesture::Pan { dx, dy });
+    }
+
+    let (v0, v1) = ((b0.0 - a0.0, b0.1 - a0.1), (b1.0 - a1.0, b1.1 - a1.1));
+    let (len0, len1) = (v0.0.hypot(v0.1), v1.0.hypot(v1.1));
+    if len0 > 0.0 && len1 > 0.0 {
+        if len1 != len0 {
+            gestures.push(Gesture::Pinch { scale: len1 / len0 });
+        }
+
+        // Screen y points down, flip it so positive is counterclockwise.
+        let angle = |v: (f32, f32)| (-v.1).atan2(v.0);
+        let mut radians = angle(v1) - angle(v0);
+        if radians > std::f32::consts::PI {
+            radians -= std::f32::consts::TAU;
+        } else if radians < -std::f32::consts::PI {
+            radians += std::f32::consts::TAU;
+        }
+        if radians != 0.0 {
+            gestures.push(Gesture::Rotate { radians });
+        }
+    }
+
+    gestures
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::{dpi::PhysicalPosition, event::DeviceId};
+
+    use super::*;
+
+    fn touch(
+        tracker: &mut TouchTracker,
+        id: u64,
+        phase: TouchPhase,
+        x: f64,
+        y: f64,
+    ) -> Vec<Gesture> {
+        tracker.on_touch(Touch {
+            // Only compared, never passed back to winit.
+            device_id: unsafe { DeviceId::dummy() },
+            phase,
+            location: PhysicalPosition::new(x, y),
+            force: None,
+            id,
+        })
+    }
+
+    #[test]
+    fn one_finger_pans() {
+        let mut tracker = TouchTracker::new();
+        assert!(touch(&mut tracker, 1, TouchPhase::Started, 10.0, 10.0).is_empty());
+        assert_eq!(
+            touch(&mut tracker, 1, TouchPhase::Moved, 15.0, 8.0),
+            [Gesture::Pan { dx: 5.0, dy: -2.0 }]
+        );
+        // A touch that started while the window was unfocused is picked up
+        // on its first move, without a jump.
+        tracker.clear();
+        assert!(touch(&mut tracker, 2, TouchPhase::Moved, 0.0, 0.0).is_empty());
+        assert_eq!(tracker.touch_count(), 1);
+    }
+
+    #[test]
+    fn two_fingers_pan_pinch_and_rotate() {
+        let mut tracker = TouchTracker::new();
+        touch(&mut tracker, 1, TouchPhase::Started, 0.0, 0.0);
+        touch(&mut tracker, 2, TouchPhase::Started, 10.0, 0.0);
+
+        // Spreading the fingers pinches around their center.
+        assert_eq!(
+            touch(&mut tracker, 1, TouchPhase::Moved, -5.0, 0.0),
+            [
+                Gesture::Pan { dx: -2.5, dy: 0.0 },
+                Gesture::Pinch { scale: 1.5 }
+            ]
+        );
+        assert_eq!(
+            touch(&mut tracker, 2, TouchPhase::Moved, 15.0, 0.0),
+            [
+                Gesture::Pan { dx: 2.5, dy: 0.0 },
+                Gesture::Pinch { scale: 20.0 / 15.0 }
+            ]
+        );
+
+        // Moving the second finger up rotates counterclockwise.
+        let gestures = touch(&mut tracker, 2, TouchPhase::Moved, -5.0, -20.0);
+        let Some(Gesture::Rotate { radians }) = gestures.last() else {
+            panic!("{gestures:?}");
+        };
+        assert!((radians - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn extra_fingers_are_ignored() {
+        let mut tracker = TouchTracker::new();
+        touch(&mut tracker, 1, TouchPhase::Started, 0.0, 0.0);
+        touch(&mut tracker, 2, TouchPhase::Started, 10.0, 0.0);
+        touch(&mut tracker, 3, TouchPhase::Started, 50.0, 50.0);
+        assert!(touch(&mut tracker, 3, TouchPhase::Moved, 60.0, 90.0).is_empty());
+        assert_eq!(tracker.touch_count(), 3);
+    }
+
+    #[test]
+    fn lifted_fingers_stop_gesturing() {
+        let mut tracker = TouchTracker::new();
+        touch(&mut tracker, 1, TouchPhase::Started, 0.0, 0.0);
+        touch(&mut tracker, 2, TouchPhase::Started, 10.0, 0.0);
+        assert!(touch(&mut tracker, 2, TouchPhase::Ended, 10.0, 0.0).is_empty());
+        assert_eq!(tracker.touch_count(), 1);
+
+        // The remaining finger pans on its own without jumping.
+        assert_eq!(
+            touch(&mut tracker, 1, TouchPhase::Moved, 1.0, 0.0),
+            [Gesture::Pan { dx: 1.0, dy: 0.0 }]
+        );
+
+        touch(&mut tracker, 1, TouchPhase::Cancelled, 1.0, 0.0);
+        assert_eq!(tracker.touch_count(), 0);
+        assert!(touch(&mut tracker, 1, TouchPhase::Moved, 5.0, 0.0).is_empty());
+
+        touch(&mut tracker, 1, TouchPhase::Started, 0.0, 0.0);
+        touch(&mut tracker, 2, TouchPhase::Started, 10.0, 0.0);
+        tracker.clear();
+        assert_eq!(tracker.touch_count(), 0);
+    }
+
+    #[test]
+    fn rotation_wraps_around() {
+        // From just below the negative x axis to just above it is a small
+        // clockwise turn, not almost a full one.
+        let gestures = gestures_between(((0.0, 0.0), (-10.0, 0.1)), ((0.0, 0.0), (-10.0, -0.1)));
+        let Some(Gesture::Rotate { radians }) = gestures.last() else {
+            panic!("{gestures:?}");
+        };
+        assert!(radians.abs() < 0.1, "{radians}");
+        assert!(*radians < 0.0);
+
+        assert!(gestures_between(((0.0, 0.0), (1.0, 1.0)), ((0.0, 0.0), (1.0, 1.0))).is_empty());
+    }
+}
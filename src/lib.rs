@@ -1,59 +1,151 @@
 use std::{cell::RefCell, rc::Rc};
 
+use config::Overrides;
+pub use config::Config;
 use context::Context;
 use demo::Demo;
-use resources::fs::{load_json, save_json};
+use input::{
+    gamepad::{GamepadEvent, Gamepads},
+    record::{InputEvent, Player, Recorder, Recording},
+    touch::{self, TouchTracker},
+    Button, InputMap,
+};
+use resources::fs::save_json;
 use winit::{
-    event::{DeviceEvent, ElementState, Event, MouseScrollDelta, StartCause, WindowEvent},
+    dpi::PhysicalSize,
+    event::{DeviceEvent, ElementState, Event, KeyEvent, MouseScrollDelta, StartCause, WindowEvent},
     event_loop::EventLoop,
+    keyboard::PhysicalKey,
 };
 
+pub mod config;
 pub mod context;
 pub mod demo;
+pub mod input;
 pub mod resources;
-mod window;
-
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
-pub struct Config {
-    width: u32,
-    height: u32,
-    scroll_sensitivity: f32,
-    touch_sensitivity: f32,
-    fullscreen: bool,
-    monitor: Option<String>,
+pub mod window;
+
+const CONFIG_PATH: &str = "user:config.json";
+/// Where the config was kept before it moved to the user directory,
+/// relative to the working directory.
+const LEGACY_CONFIG_PATH: &str = "config.json";
+
+/// Settings that don't come from the config file, usually parsed from the
+/// command line.
+#[derive(Debug, Default)]
+pub struct Options {
+    /// Config file to use instead of `config.json` in the user directory.
+    pub config_path: Option<String>,
+    /// Applied on top of the config file and `WGPU_TEMPLATE_*` env vars.
+    pub overrides: Overrides,
+    /// Print the effective config and exit without opening a window.
+    pub print_config: bool,
+    /// Record all input to this file, saved on exit.
+    pub record: Option<String>,
+    /// Drive the demo from a recording instead of live input.
+    pub replay: Option<String>,
+    /// Replay without a window and print a hash of every frame.
+    pub headless: bool,
+    /// Seconds per update instead of the measured or recorded frame time.
+    pub timestep: Option<f32>,
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            width: 1920,
-            height: 1080,
-            scroll_sensitivity: 0.5,
-            touch_sensitivity: 0.1,
-            monitor: None,
-            fullscreen: false,
+/// Config precedence, lowest to highest: defaults, config file,
+/// `WGPU_TEMPLATE_*` env vars, [`Options::overrides`]. Overrides aren't
+/// written back to the config file on exit.
+pub async fn run(options: Options) -> anyhow::Result<()> {
+    let config_path = options.config_path.as_deref().unwrap_or(CONFIG_PATH);
+    #[cfg_attr(not(feature = "hot-reload"), allow(unused_mut))]
+    let mut file_config = match load_config(config_path).await {
+        Ok(config) => config,
+        Err(e) => {
+            log::warn!("Using default config, unable to load {config_path}: {e}");
+            Config::default()
         }
+    };
+
+    let mut overrides = Overrides::from_env();
+    overrides.extend(options.overrides);
+    overrides.validate()?;
+    let config = overrides.apply(&file_config);
+
+    if options.print_config {
+        println!("{}", serde_json::to_string_pretty(&config)?);
+        return Ok(());
     }
-}
 
-pub async fn run() -> anyhow::Result<()> {
-    let config: Config = match load_json("config.json").await {
-        Ok(config) => config,
-        Err(_) => Default::default(),
+    let player = match &options.replay {
+        Some(path) => Some(Player::new(Recording::load(path).await?)),
+        None => None,
     };
 
+    if options.headless {
+        let Some(player) = player else {
+            anyhow::bail!("Running headless needs a recording to replay");
+        };
+        return run_headless(&config, player, options.timestep).await;
+    }
+
     let event_loop = EventLoop::new()?;
     let window = window::Window::new(&config, &event_loop)?;
 
-    let mut context = Context::new(&window).await?;
-    let mut demo = Demo::new(&context, config.width, config.height)?;
+    let context = Context::new(&window).await?;
+    let size = window.inner_size();
+    let mut demo = Demo::new(&context, size.width, size.height, window.scale_factor())?;
+    demo.configure(&config);
+    demo.load_camera_path().await;
 
+    #[cfg(feature = "hot-reload")]
+    let watcher = {
+        let proxy = event_loop.create_proxy();
+        let mut watcher = resources::watcher::AssetWatcher::new(
+            std::time::Duration::from_millis(250),
+            move || {
+                let _ = proxy.send_event(());
+            },
+        )?;
+        watcher.watch(config_path)?;
+        // The shaders are watched in the source tree, which isn't there
+        // when running an installed build.
+        for path in demo.watched_assets() {
+            if let Err(e) = watcher.watch(path) {
+                log::warn!("Not watching {path}: {e}");
+            }
+        }
+        watcher
+    };
+
+    let recorder = options.record.as_ref().map(|path| {
+        let recorder = Recorder::new(
+            size.width,
+            size.height,
+            window.scale_factor(),
+            config.bindings.clone(),
+        );
+        recorder.save_on_panic(path.clone());
+        recorder
+    });
+    let replaying = player.is_some();
+    let mut session = Session {
+        input: InputMap::new(config.bindings.clone()),
+        context,
+        demo,
+        recorder,
+        player,
+    };
+    let mut touches = TouchTracker::new();
+    let mut gamepads = Gamepads::standard(config.dead_zones);
     let config = Rc::new(RefCell::new(config));
     let final_config = config.clone();
+    let recording = Rc::new(RefCell::new(None));
+    let final_recording = recording.clone();
+
+    let mut focused = true;
+    let mut last_update = instant::Instant::now();
 
     let window = &window;
     event_loop.run(move |event, target| {
-        if !demo.running {
+        if !session.demo.running {
             target.exit();
         }
 
@@ -66,22 +158,91 @@ pub async fn run() -> anyhow::Result<()> {
                     log::info!("ActivationTokenDone {{ {serial:?}, {token:?} }}");
                 }
                 WindowEvent::Resized(size) => {
-                    context.resize(size.width, size.height);
-                    demo.resize(&context, size.width, size.height);
+                    session.on_live(InputEvent::Resized {
+                        width: size.width,
+                        height: size.height,
+                    });
+                    window.modify_config(&mut final_config.borrow_mut());
                 }
-                WindowEvent::CloseRequested | WindowEvent::Destroyed => {
-                    if demo.close() {
-                        target.exit()
+                WindowEvent::ScaleFactorChanged {
+                    scale_factor,
+                    mut inner_size_writer,
+                } => {
+                    // Keep the logical size, the window moved to a monitor
+                    // with a different scale factor.
+                    let mut size = window.inner_size();
+                    if window.is_windowed() {
+                        size = window::Window::configured_size(&final_config.borrow(), scale_factor);
+                        let _ = inner_size_writer.request_inner_size(size);
                     }
+                    session.on_live(InputEvent::Resized {
+                        width: size.width,
+                        height: size.height,
+                    });
+                    session.on_live(InputEvent::ScaleFactorChanged { scale_factor });
+                }
+                WindowEvent::Moved(_) => {
+                    window.modify_config(&mut final_config.borrow_mut());
+                }
+                WindowEvent::CloseRequested | WindowEvent::Destroyed if session.demo.close() => {
+                    target.exit()
+                }
+                WindowEvent::Focused(new_focused) => {
+                    focused = new_focused;
+                    window.set_focused(focused);
+                    gamepads.set_focused(focused);
+                    if !focused {
+                        touches.clear();
+                    }
+                    session.on_live(InputEvent::Focused(focused));
+                }
+                WindowEvent::ModifiersChanged(modifiers) => {
+                    session.on_live(InputEvent::Modifiers(modifiers.state().into()));
+                }
+                WindowEvent::KeyboardInput {
+                    event:
+                        KeyEvent {
+                            physical_key: PhysicalKey::Code(key),
+                            state,
+                            repeat: false,
+                            ..
+                        },
+                    ..
+                } => {
+                    session.on_live(InputEvent::Button {
+                        button: Button::Key(key),
+                        pressed: state == ElementState::Pressed,
+                    });
+                }
+                WindowEvent::MouseInput { state, button, .. } => {
+                    session.on_live(InputEvent::Button {
+                        button: Button::Mouse(button),
+                        pressed: state == ElementState::Pressed,
+                    });
                 }
                 WindowEvent::CursorMoved { position, .. } => {
-                    demo.on_cursor_moved(position.x, position.y);
+                    session.on_live(InputEvent::CursorMoved {
+                        x: position.x,
+                        y: position.y,
+                    });
                 }
                 WindowEvent::CursorEntered { .. } => {
-                    demo.on_cursor_entered();
+                    session.on_live(InputEvent::CursorEntered);
+                }
+                WindowEvent::CursorLeft { .. } => session.on_live(InputEvent::CursorLeft),
+                WindowEvent::Touch(touch) => {
+                    for gesture in touches.on_touch(touch) {
+                        session.on_live(InputEvent::Gesture(gesture));
+                    }
+                }
+                WindowEvent::TouchpadMagnify { delta, .. } => {
+                    session.on_live(InputEvent::Gesture(touch::touchpad_pinch(delta)));
+                }
+                WindowEvent::TouchpadRotate { delta, .. } => {
+                    session.on_live(InputEvent::Gesture(touch::touchpad_rotate(delta)));
                 }
-                WindowEvent::CursorLeft { .. } => demo.on_cursor_left(),
                 WindowEvent::RedrawRequested => {
+                    let Session { context, demo, .. } = &mut session;
                     context.render(|frame, context| {
                         demo.render(frame, context);
                     });
@@ -90,44 +251,304 @@ pub async fn run() -> anyhow::Result<()> {
             },
             Event::DeviceEvent { device_id, event } => match event {
                 DeviceEvent::Added => {
-                    demo.on_device_added(device_id);
+                    session.demo.on_device_added(device_id);
                 }
                 DeviceEvent::Removed => {
-                    demo.on_device_removed(device_id);
+                    session.demo.on_device_removed(device_id);
                 }
+                // Device events arrive regardless of focus, don't let them
+                // drive the demo from other windows.
+                _ if !focused => {}
                 DeviceEvent::MouseWheel { delta } => {
-                    let (x, y) = match delta {
-                        MouseScrollDelta::LineDelta(x, y) => {
-                            let config = final_config.borrow();
-                            (x * config.scroll_sensitivity, y * config.scroll_sensitivity)
+                    let (x, y) = {
+                        let config = final_config.borrow();
+                        match delta {
+                            MouseScrollDelta::LineDelta(x, y) => {
+                                (x * config.scroll_sensitivity, y * config.scroll_sensitivity)
+                            }
+                            // Touchpads scroll in pixels, which are a lot
+                            // smaller than lines.
+                            MouseScrollDelta::PixelDelta(delta) => (
+                                delta.x as f32 * config.touch_sensitivity,
+                                delta.y as f32 * config.touch_sensitivity,
+                            ),
                         }
-                        MouseScrollDelta::PixelDelta(_) => todo!(),
                     };
-                    demo.on_mouse_scoll(x, y);
+                    session.on_live(InputEvent::Scroll { x, y });
                 }
                 DeviceEvent::Motion { axis, value } => {
-                    demo.on_axis(axis, value);
+                    session.on_live(InputEvent::Motion { axis, value });
                 }
                 DeviceEvent::Button { button, state } => {
-                    demo.on_button(button, state == ElementState::Pressed);
+                    session.on_live(InputEvent::DeviceButton {
+                        button,
+                        pressed: state == ElementState::Pressed,
+                    });
                 }
                 DeviceEvent::Key(key) => {
-                    demo.on_key(key.physical_key, key.state == ElementState::Pressed);
+                    session.on_live(InputEvent::DeviceKey {
+                        key: key.physical_key,
+                        pressed: key.state == ElementState::Pressed,
+                    });
                 }
                 _ => (),
             },
+            #[cfg(feature = "hot-reload")]
+            Event::UserEvent(()) => {
+                use pollster::FutureExt;
+
+                for path in watcher.changed() {
+                    // A file that is still being written usually fails to
+                    // parse. Keep the old version around, the next write
+                    // triggers another reload.
+                    if path == config_path {
+                        match Config::load(&path).block_on() {
+                            Ok(config) => {
+                                let applied = overrides.apply(&config);
+                                let previous = overrides.apply(&file_config);
+                                for command in window::Command::for_reload(&previous, &applied) {
+                                    window.apply(command);
+                                }
+                                session.on_live(InputEvent::Bindings(applied.bindings.clone()));
+                                gamepads.set_dead_zones(applied.dead_zones);
+                                session.demo.configure(&applied);
+                                *final_config.borrow_mut() = applied;
+                                window.modify_config(&mut final_config.borrow_mut());
+                                file_config = config;
+                                log::info!("Reloaded {path}");
+                            }
+                            Err(e) => log::warn!("Unable to reload {path}: {e}"),
+                        }
+                    } else if let Err(e) = session.demo.reload_asset(&session.context, &path).block_on() {
+                        log::warn!("Unable to reload {path}: {e}");
+                    } else {
+                        log::info!("Reloaded {path}");
+                    }
+                    window.request_redraw();
+                }
+            }
             // winit::event::Event::Suspended => todo!(),
             // winit::event::Event::Resumed => todo!(),
-            // winit::event::Event::AboutToWait => todo!(),
+            Event::AboutToWait => {
+                for event in gamepads.poll() {
+                    session.on_live(InputEvent::Gamepad(event));
+                }
+
+                let now = instant::Instant::now();
+                let mut dt = (now - last_update).as_secs_f32();
+                last_update = now;
+                if replaying {
+                    match session.replay_frame(Some(window)) {
+                        Some(recorded) => dt = recorded,
+                        None => {
+                            log::info!("Replay finished");
+                            target.exit();
+                            return;
+                        }
+                    }
+                }
+                let dt = options.timestep.unwrap_or(dt);
+
+                if session.input.just_pressed(input::TOGGLE_FULLSCREEN) {
+                    window.toggle_fullscreen();
+                }
+                if session.input.just_pressed(input::NEXT_MONITOR) {
+                    window.next_monitor();
+                }
+
+                session.update(dt);
+
+                for command in session.demo.take_window_commands() {
+                    if let Some(mode) = window.apply(command) {
+                        session.on_live(InputEvent::CursorMode(mode));
+                    }
+                }
+                window.request_redraw();
+            }
             // winit::event::Event::MemoryWarning => todo!(),
             Event::LoopExiting => {
-                window.modify_config(&mut final_config.borrow_mut());
+                session.demo.stop_path_recording();
+                session.demo.finish_path_saving();
+                let mut config = final_config.borrow_mut();
+                window.modify_config(&mut config);
+                config.bindings = session.input.bindings().clone();
+                *config = overrides.revert(&config, &file_config);
+                *final_recording.borrow_mut() = session.recorder.take().map(Recorder::finish);
             }
             _ => {}
         }
     })?;
 
-    save_json("config.json", &*config.borrow()).await?;
+    if let (Some(path), Some(recording)) = (&options.record, recording.take()) {
+        recording.save(path).await?;
+        log::info!("Saved {} frames to {path}", recording.frame_count());
+    }
+
+    let config = config.borrow().clone();
+    if config.is_newer() {
+        log::warn!("Not saving {config_path}, it is from a newer version");
+    } else if !replaying {
+        // A replay resizes the window and rebinds input, don't keep any of
+        // it.
+        save_json(config_path, &config).await?;
+    }
 
     Ok(())
 }
+
+/// Loads the config at `config_path`. The default path falls back to
+/// [`LEGACY_CONFIG_PATH`] until there is a config in the user directory,
+/// which happens as soon as it's saved on exit.
+async fn load_config(config_path: &str) -> anyhow::Result<Config> {
+    if config_path == CONFIG_PATH {
+        let user_path = resources::vfs::get().real_path(CONFIG_PATH);
+        let legacy_path = std::env::current_dir()?.join(LEGACY_CONFIG_PATH);
+        if user_path.is_some_and(|path| !path.exists()) && legacy_path.is_file() {
+            log::info!("Moving {legacy_path:?} to {CONFIG_PATH}");
+            return Config::load(&legacy_path.to_string_lossy()).await;
+        }
+    }
+    Config::load(config_path).await
+}
+
+/// Replays `player` without a window and prints a hash of every frame, so
+/// two runs can be compared.
+async fn run_headless(config: &Config, player: Player, timestep: Option<f32>) -> anyhow::Result<()> {
+    // The recording starts with the real size, this is just a placeholder.
+    let context = Context::headless(config.width, config.height).await?;
+    let mut demo = Demo::new(&context, config.width, config.height, 1.0)?;
+    demo.configure(config);
+    demo.load_camera_path().await;
+    let mut session = Session {
+        input: InputMap::new(config.bindings.clone()),
+        context,
+        demo,
+        recorder: None,
+        player: Some(player),
+    };
+
+    while session.demo.running {
+        let frame = session.player.as_ref().map_or(0, Player::frame);
+        let Some(dt) = session.replay_frame(None) else {
+            break;
+        };
+
+        session.update(timestep.unwrap_or(dt));
+        // There is no window to apply them to.
+        session.demo.take_window_commands();
+        let Session { context, demo, .. } = &mut session;
+        context.render(|frame, context| {
+            demo.render(frame, context);
+        });
+
+        let pixels = session.context.read_pixels().await?;
+        println!("frame {frame}: {}", blake3::hash(&pixels));
+    }
+    session.demo.finish_path_saving();
+
+    Ok(())
+}
+
+/// The demo and everything that feeds it input. Live events go through
+/// [`Session::on_live`], which records them if recording. While replaying
+/// they are ignored and the recorded events are used instead.
+struct Session<'a> {
+    context: Context<'a>,
+    demo: Demo,
+    input: InputMap,
+    recorder: Option<Recorder>,
+    player: Option<Player>,
+}
+
+impl Session<'_> {
+    fn on_live(&mut self, event: InputEvent) {
+        // The surface has to match the window, even while replaying.
+        let window_event = matches!(
+            event,
+            InputEvent::Resized { .. } | InputEvent::ScaleFactorChanged { .. }
+        );
+        if self.player.is_some() && !window_event {
+            return;
+        }
+
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(event.clone());
+        }
+        self.forward(&event);
+    }
+
+    /// Forwards the events of the next recorded frame and returns its frame
+    /// time. With a window, recorded sizes are requested from the window
+    /// instead of applied directly.
+    fn replay_frame(&mut self, window: Option<&window::Window>) -> Option<f32> {
+        let (dt, events) = self.player.as_mut()?.next_frame()?;
+        for event in events {
+            match (&event, window) {
+                (InputEvent::Resized { width, height }, Some(window)) => {
+                    let _ = window.request_inner_size(PhysicalSize::new(*width, *height));
+                }
+                (InputEvent::ScaleFactorChanged { .. }, Some(_)) => {}
+                _ => self.forward(&event),
+            }
+        }
+        Some(dt)
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.demo.update(&self.input, dt);
+        self.input.end_frame();
+        if let Some(recorder) = &mut self.recorder {
+            recorder.end_frame(dt);
+        }
+    }
+
+    fn forward(&mut self, event: &InputEvent) {
+        let Self { context, demo, input, .. } = self;
+        match event {
+            InputEvent::Resized { width, height } => {
+                context.resize(*width, *height);
+                demo.resize(context, *width, *height);
+            }
+            InputEvent::ScaleFactorChanged { scale_factor } => {
+                demo.set_scale_factor(*scale_factor);
+            }
+            InputEvent::Focused(focused) => {
+                if !focused {
+                    input.release_all();
+                }
+            }
+            InputEvent::Bindings(bindings) => input.set_bindings(bindings.clone()),
+            InputEvent::Modifiers(modifiers) => input.on_modifiers(*modifiers),
+            InputEvent::Button { button, pressed } => input.on_button(*button, *pressed),
+            InputEvent::CursorMoved { x, y } => demo.on_cursor_moved(*x, *y),
+            InputEvent::CursorEntered => demo.on_cursor_entered(),
+            InputEvent::CursorLeft => demo.on_cursor_left(),
+            InputEvent::Gesture(gesture) => demo.on_gesture(*gesture),
+            InputEvent::Scroll { x, y } => {
+                input.on_scroll(*x, *y);
+                demo.on_mouse_scoll(*x, *y);
+            }
+            InputEvent::Motion { axis, value } => {
+                input.on_motion(*axis, *value);
+                demo.on_axis(*axis, *value);
+            }
+            InputEvent::DeviceButton { button, pressed } => demo.on_button(*button, *pressed),
+            InputEvent::DeviceKey { key, pressed } => demo.on_key(*key, *pressed),
+            InputEvent::CursorMode(mode) => demo.on_cursor_mode(*mode),
+            InputEvent::Gamepad(event) => {
+                match event {
+                    GamepadEvent::Connected { id, name } => {
+                        log::info!("Gamepad {id} connected: {name}");
+                        demo.on_gamepad_connected(*id, name);
+                    }
+                    GamepadEvent::Disconnected { id } => {
+                        log::info!("Gamepad {id} disconnected");
+                        demo.on_gamepad_disconnected(*id);
+                    }
+                    _ => {}
+                }
+                input.on_gamepad(event);
+            }
+        }
+    }
+}
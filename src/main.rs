@@ -1,6 +1,199 @@
 use pollster::FutureExt;
-use wgpu_template::run;
+use wgpu_template::{run, Options};
+
+const USAGE: &str = "\
+Usage: wgpu-template [options]
+
+Options:
+  --config <path>     Use <path> instead of config.json in the user directory
+  --print-config      Print the effective config and exit
+  --record <path>     Record all input to <path> (.json, .ron or .bin)
+  --replay <path>     Replay recorded input instead of using live input
+  --headless          Replay without a window, printing a hash per frame
+  --timestep <secs>   Use a fixed frame time instead of the measured or
+                      recorded one
+  --<field> <value>   Override a config field, e.g. --width 1280 --monitor DP-1
+  --<field>           Set a boolean field to true, e.g. --fullscreen
+  --no-<field>        Set a boolean field to false
+  -h, --help          Print this message
+
+Config fields can also be set with WGPU_TEMPLATE_<FIELD> env vars. The
+command line takes precedence over env vars, which take precedence over the
+config file.";
 
 fn main() -> anyhow::Result<()> {
-   run().block_on()
-}
\ No newline at end of file
+    env_logger::init();
+
+    let Some(options) = parse_args(std::env::args().skip(1))? else {
+        println!("{USAGE}");
+        return Ok(());
+    };
+
+    run(options).block_on()
+}
+
+/// Returns `None` if help was requested.
+fn parse_args(args: impl Iterator<Item = String>) -> anyhow::Result<Option<Options>> {
+    let mut options = Options::default();
+    let mut args = args.peekable();
+
+    while let Some(arg) = args.next() {
+        let Some(name) = arg.strip_prefix("--") else {
+            if arg == "-h" {
+                return Ok(None);
+            }
+            anyhow::bail!("Unexpected argument {arg:?}\n\n{USAGE}");
+        };
+
+        let (name, inline_value) = match name.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_owned())),
+            None => (name, None),
+        };
+
+        match name {
+            "help" => return Ok(None),
+            "print-config" => options.print_config = true,
+            "headless" => options.headless = true,
+            "config" => options.config_path = Some(path_arg(name, inline_value, &mut args)?),
+            "record" => options.record = Some(path_arg(name, inline_value, &mut args)?),
+            "replay" => options.replay = Some(path_arg(name, inline_value, &mut args)?),
+            "timestep" => {
+                let value = inline_value
+                    .or_else(|| args.next())
+                    .ok_or_else(|| anyhow::anyhow!("--timestep needs a value"))?;
+                let timestep: f32 = value.parse()?;
+                if !timestep.is_finite() || timestep <= 0.0 {
+                    anyhow::bail!("--timestep must be positive");
+                }
+                options.timestep = Some(timestep);
+            }
+            _ => {
+                if let (Some(field), None) = (name.strip_prefix("no-"), &inline_value) {
+                    set_override(&mut options, field, "false")?;
+                    continue;
+                }
+                let value = match inline_value {
+                    Some(value) => value,
+                    None if args.peek().is_none_or(|next| next.starts_with("--")) => {
+                        "true".to_owned()
+                    }
+                    None => args.next().unwrap_or_default(),
+                };
+                set_override(&mut options, name, &value)?;
+            }
+        }
+    }
+
+    Ok(Some(options))
+}
+
+fn set_override(options: &mut Options, field: &str, value: &str) -> anyhow::Result<()> {
+    options
+        .overrides
+        .set(field, value)
+        .map_err(|e| anyhow::anyhow!("--{field}: {e}\n\n{USAGE}"))?;
+    Ok(())
+}
+
+/// Paths are relative to the working directory, not the vfs.
+fn path_arg(
+    name: &str,
+    inline_value: Option<String>,
+    args: &mut impl Iterator<Item = String>,
+) -> anyhow::Result<String> {
+    let path = inline_value
+        .or_else(|| args.next())
+        .ok_or_else(|| anyhow::anyhow!("--{name} needs a path"))?;
+    let path = std::env::current_dir()?.join(path);
+    Ok(path.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+    use wgpu_template::Config;
+
+    use super::*;
+
+    fn parse(args: &[&str]) -> anyhow::Result<Option<Options>> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn field(options: &Options, field: &str) -> Value {
+        let config = options.overrides.apply(&Config::default());
+        serde_json::to_value(config).unwrap()[field].clone()
+    }
+
+    #[test]
+    fn parses_options() {
+        let options = parse(&["--headless", "--replay", "input.json", "--timestep=0.5"])
+            .unwrap()
+            .unwrap();
+        assert!(options.headless);
+        assert!(options.overrides.is_empty());
+        assert_eq!(options.timestep, Some(0.5));
+        // Made absolute so they don't go through the vfs.
+        let replay = options.replay.unwrap();
+        assert!(std::path::Path::new(&replay).is_absolute());
+        assert!(replay.ends_with("input.json"));
+
+        assert!(parse(&["--help"]).unwrap().is_none());
+        assert!(parse(&["-h"]).unwrap().is_none());
+    }
+
+    #[test]
+    fn parses_overrides() {
+        let options = parse(&[
+            "--width",
+            "800",
+            "--height=600",
+            "--fullscreen",
+            "--no-maximized",
+            "--scroll-sensitivity",
+            "2",
+            "--monitor",
+            "DP-1",
+        ])
+        .unwrap()
+        .unwrap();
+        options.overrides.validate().unwrap();
+        assert_eq!(field(&options, "width"), 800);
+        assert_eq!(field(&options, "height"), 600);
+        assert_eq!(field(&options, "fullscreen"), true);
+        assert_eq!(field(&options, "maximized"), false);
+        assert_eq!(field(&options, "scroll_sensitivity"), 2.0);
+        assert_eq!(field(&options, "monitor"), "DP-1");
+    }
+
+    #[test]
+    fn later_overrides_win() {
+        let options = parse(&["--width", "800", "--width", "640"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(field(&options, "width"), 640);
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        for args in [
+            &["--widht", "800"][..],
+            &["--no-such-field"],
+            &["--colour=blue"],
+            &["input.json"],
+            &["--timestep", "-1"],
+            &["--timestep", "fast"],
+            &["--replay"],
+        ] {
+            assert!(parse(args).is_err(), "{args:?}");
+        }
+
+        let error = parse(&["--widht", "800"]).unwrap_err().to_string();
+        assert!(error.contains("widht"));
+        assert!(error.contains("Usage:"));
+
+        // Known field, bad value. This is caught once the overrides are
+        // validated.
+        let options = parse(&["--width", "0"]).unwrap().unwrap();
+        assert!(options.overrides.validate().is_err());
+    }
+}
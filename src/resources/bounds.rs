@@ -0,0 +1,128 @@
+/// Axis aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: glam::Vec3,
+    pub max: glam::Vec3,
+}
+
+impl Aabb {
+    pub const fn new(min: glam::Vec3, max: glam::Vec3) -> Self {
+        Self { min, max }
+    }
+
+    /// `None` if there are no points.
+    pub fn from_points(points: impl IntoIterator<Item = glam::Vec3>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Self::new(first, first), |aabb, p| Self {
+            min: aabb.min.min(p),
+            max: aabb.max.max(p),
+        }))
+    }
+
+    pub fn center(&self) -> glam::Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn half_extents(&self) -> glam::Vec3 {
+        (self.max - self.min) * 0.5
+    }
+
+    pub fn contains(&self, point: glam::Vec3) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// The smallest sphere around the box.
+    pub fn bounding_sphere(&self) -> Sphere {
+        Sphere::new(self.center(), self.half_extents().length())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sphere {
+    pub center: glam::Vec3,
+    pub radius: f32,
+}
+
+impl Sphere {
+    pub const fn new(center: glam::Vec3, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    pub fn contains(&self, point: glam::Vec3) -> bool {
+        self.center.distance_squared(point) <= self.radius * self.radius
+    }
+}
+
+/// The points where `normal.dot(p) + d == 0`. `normal` points to the
+/// positive side.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub normal: glam::Vec3,
+    pub d: f32,
+}
+
+impl Plane {
+    pub fn new(normal: glam::Vec3, d: f32) -> Self {
+        Self { normal, d }
+    }
+
+    pub fn from_point_normal(point: glam::Vec3, normal: glam::Vec3) -> Self {
+        Self::new(normal, -normal.dot(point))
+    }
+
+    /// Scales the plane so `normal` has unit length, making
+    /// [`Plane::signed_distance`] an actual distance. Planes without a
+    /// normal are left alone.
+    pub fn normalize(self) -> Self {
+        let length = self.normal.length();
+        if length <= f32::EPSILON {
+            return self;
+        }
+        Self::new(self.normal / length, self.d / length)
+    }
+
+    pub fn signed_distance(&self, point: glam::Vec3) -> f32 {
+        self.normal.dot(point) + self.d
+    }
+}
+
+/// Anything that can be culled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bounds {
+    Point(glam::Vec3),
+    Sphere(Sphere),
+    Aabb(Aabb),
+}
+
+impl From<glam::Vec3> for Bounds {
+    fn from(point: glam::Vec3) -> Self {
+        Self::Point(point)
+    }
+}
+
+impl From<Sphere> for Bounds {
+    fn from(sphere: Sphere) -> Self {
+        Self::Sphere(sphere)
+    }
+}
+
+impl From<Aabb> for Bounds {
+    fn from(aabb: Aabb) -> Self {
+        Self::Aabb(aabb)
+    }
+}
+
+/// Implemented by things that get drawn, so they can be culled with
+/// [`super::camera::frustum::Frustum::cull`].
+pub trait Bounded {
+    /// In world space.
+    fn bounds(&self) -> Bounds;
+}
@@ -36,6 +36,10 @@ impl<T: bytemuck::Pod + bytemuck::Zeroable> CpuBuffer<T> {
     pub fn len(&self) -> u32 {
         self.data.len() as _
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
 }
 
 pub struct BufferBatch<'a, T: bytemuck::Pod + bytemuck::Zeroable> {
@@ -0,0 +1,229 @@
+use crate::input::{self, InputMap};
+
+use super::Camera;
+
+/// Movement speeds are in units per second.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct FlySettings {
+    pub speed: f32,
+    /// Multiplies `speed` while [`input::MOVE_FAST`] is held.
+    pub fast_multiplier: f32,
+    /// How quickly the velocity approaches the target speed, in units per
+    /// second squared.
+    pub acceleration: f32,
+    /// How quickly the camera comes to a stop once nothing is held. Higher
+    /// is snappier, 0 drifts forever.
+    pub damping: f32,
+}
+
+impl Default for FlySettings {
+    fn default() -> Self {
+        Self {
+            speed: 4.0,
+            fast_multiplier: 4.0,
+            acceleration: 40.0,
+            damping: 10.0,
+        }
+    }
+}
+
+/// First person controls for a [`Camera`]: movement from the `move_*` axes,
+/// turning from the `turn_*` axes and, while `mouse_look` is set, looking
+/// around with the `look_*` axes.
+#[derive(Debug, Default)]
+pub struct FlyController {
+    pub settings: FlySettings,
+    pub mouse_look: bool,
+    /// Right, up and forward relative to the camera.
+    velocity: glam::Vec3,
+}
+
+impl FlyController {
+    pub fn new(settings: FlySettings) -> Self {
+        Self {
+            settings,
+            ..Default::default()
+        }
+    }
+
+    pub fn velocity(&self) -> glam::Vec3 {
+        self.velocity
+    }
+
+    pub fn stop(&mut self) {
+        self.velocity = glam::Vec3::ZERO;
+    }
+
+    pub fn update(&mut self, camera: &mut Camera, input: &InputMap, dt: f32) {
+        // Look deltas are already distances, turn rates need the frame time.
+        let (mut yaw, mut pitch) = (0.0, 0.0);
+        if self.mouse_look {
+            yaw += input.value(input::LOOK_X);
+            pitch += input.value(input::LOOK_Y);
+        }
+        yaw += input.value(input::TURN_X) * dt;
+        pitch += input.value(input::TURN_Y) * dt;
+        if yaw != 0.0 {
+            camera.rotate_right(yaw);
+        }
+        if pitch != 0.0 {
+            camera.rotate_up(pitch);
+        }
+
+        let direction = glam::vec3(
+            input.value(input::MOVE_RIGHT),
+            input.value(input::MOVE_UP),
+            input.value(input::MOVE_FORWARD),
+        )
+        .clamp_length_max(1.0);
+
+        let mut speed = self.settings.speed;
+        if input.pressed(input::MOVE_FAST) {
+            speed *= self.settings.fast_multiplier;
+        }
+
+        if direction == glam::Vec3::ZERO {
+            self.velocity *= (-self.settings.damping * dt).exp();
+        } else {
+            let target = direction * speed;
+            let change = target - self.velocity;
+            self.velocity += change.clamp_length_max(self.settings.acceleration * dt);
+        }
+
+        if self.velocity.length_squared() < 1e-8 {
+            self.velocity = glam::Vec3::ZERO;
+            return;
+        }
+
+        let movement = self.velocity * dt;
+        camera.walk_right(movement.x);
+        camera.levitate_up(movement.y);
+        camera.walk_forward(movement.z);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
+    use winit::keyboard::KeyCode;
+
+    use super::*;
+    use crate::input::{Bindings, Button};
+
+    const DT: f32 = 0.01;
+
+    /// At the origin looking along +X, so forward is +X and right is +Z.
+    fn camera() -> Camera {
+        Camera::look_at(
+            glam::Vec3::ZERO,
+            glam::Vec3::X,
+            16.0,
+            9.0,
+            FRAC_PI_4,
+            0.1,
+            100.0,
+        )
+    }
+
+    fn hold(keys: &[KeyCode]) -> InputMap {
+        let mut input = InputMap::new(Bindings::default());
+        for key in keys {
+            input.on_button(Button::Key(*key), true);
+        }
+        input
+    }
+
+    fn run(controller: &mut FlyController, camera: &mut Camera, input: &InputMap, seconds: f32) {
+        for _ in 0..(seconds / DT).round() as usize {
+            controller.update(camera, input, DT);
+        }
+    }
+
+    #[test]
+    fn accelerates_to_the_speed() {
+        let (mut controller, mut camera) = (FlyController::default(), camera());
+        let input = hold(&[KeyCode::KeyW]);
+
+        // Acceleration 40 reaches speed 4 in a tenth of a second.
+        controller.update(&mut camera, &input, DT);
+        assert!((controller.velocity().z - 0.4).abs() < 1e-5);
+        run(&mut controller, &mut camera, &input, 1.0);
+        assert!((controller.velocity().z - 4.0).abs() < 1e-5);
+
+        assert!(
+            camera.eye().x > 3.8 && camera.eye().x < 4.04,
+            "{}",
+            camera.eye()
+        );
+        assert!(camera.eye().y.abs() < 1e-5 && camera.eye().z.abs() < 1e-5);
+    }
+
+    #[test]
+    fn fast_multiplies_the_speed() {
+        let (mut controller, mut camera) = (FlyController::default(), camera());
+        run(
+            &mut controller,
+            &mut camera,
+            &hold(&[KeyCode::KeyW, KeyCode::ShiftLeft]),
+            1.0,
+        );
+        assert!((controller.velocity().z - 16.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn diagonals_are_no_faster() {
+        let (mut controller, mut camera) = (FlyController::default(), camera());
+        run(
+            &mut controller,
+            &mut camera,
+            &hold(&[KeyCode::KeyW, KeyCode::KeyD, KeyCode::KeyE]),
+            1.0,
+        );
+        assert!((controller.velocity().length() - 4.0).abs() < 1e-4);
+        assert!(camera.eye().x > 0.0 && camera.eye().y > 0.0 && camera.eye().z > 0.0);
+    }
+
+    #[test]
+    fn walking_ignores_pitch() {
+        let (mut controller, mut camera) = (FlyController::default(), camera());
+        camera.rotate_up(1.0);
+        run(&mut controller, &mut camera, &hold(&[KeyCode::KeyW]), 1.0);
+        assert!(camera.eye().x > 3.8);
+        assert!(camera.eye().y.abs() < 1e-5);
+    }
+
+    #[test]
+    fn damping_comes_to_a_full_stop() {
+        let (mut controller, mut camera) = (FlyController::default(), camera());
+        run(&mut controller, &mut camera, &hold(&[KeyCode::KeyW]), 1.0);
+
+        let input = hold(&[]);
+        controller.update(&mut camera, &input, DT);
+        assert!((controller.velocity().z - 4.0 * (-0.1f32).exp()).abs() < 1e-4);
+        run(&mut controller, &mut camera, &input, 2.0);
+        assert_eq!(controller.velocity(), glam::Vec3::ZERO);
+
+        let eye = camera.eye();
+        controller.update(&mut camera, &input, DT);
+        assert_eq!(camera.eye(), eye);
+    }
+
+    #[test]
+    fn looks_only_with_mouse_look() {
+        let (mut controller, mut camera) = (FlyController::default(), camera());
+        let mut input = hold(&[]);
+        input.on_motion(0, 100.0);
+        controller.update(&mut camera, &input, DT);
+        assert_eq!(camera.yaw(), 0.0);
+
+        // Mouse motion is a distance, it doesn't scale with the frame time.
+        controller.mouse_look = true;
+        controller.update(&mut camera, &input, 1.0);
+        let yaw = camera.yaw();
+        assert!(yaw > 0.0);
+        controller.update(&mut camera, &input, DT);
+        assert!((camera.yaw() - 2.0 * yaw).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,319 @@
+use crate::resources::bounds::{Aabb, Bounded, Bounds, Plane, Sphere};
+
+/// The volume a view-projection matrix can see, as six planes facing
+/// inwards.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    planes: [Plane; 6],
+}
+
+impl Frustum {
+    pub const LEFT: usize = 0;
+    pub const RIGHT: usize = 1;
+    pub const BOTTOM: usize = 2;
+    pub const TOP: usize = 3;
+    /// Where clip space depth is 0. That's the near plane, or the far plane
+    /// for reverse-Z projections.
+    pub const DEPTH_ZERO: usize = 4;
+    /// Where clip space depth is 1.
+    pub const DEPTH_ONE: usize = 5;
+
+    /// Extracts the planes from the rows of `view_proj`, assuming wgpu's
+    /// clip space with depth from 0 to 1. Infinite projections get a depth
+    /// plane without a normal, which everything is in front of.
+    pub fn from_matrix(view_proj: glam::Mat4) -> Self {
+        let x = view_proj.row(0);
+        let y = view_proj.row(1);
+        let z = view_proj.row(2);
+        let w = view_proj.row(3);
+        let plane = |v: glam::Vec4| Plane::new(v.truncate(), v.w).normalize();
+        Self {
+            planes: [
+                plane(w + x),
+                plane(w - x),
+                plane(w + y),
+                plane(w - y),
+                plane(z),
+                plane(w - z),
+            ],
+        }
+    }
+
+    pub fn planes(&self) -> &[Plane; 6] {
+        &self.planes
+    }
+
+    pub fn contains_point(&self, point: glam::Vec3) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(point) >= 0.0)
+    }
+
+    /// Can report spheres just outside a corner as visible.
+    pub fn intersects_sphere(&self, sphere: &Sphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(sphere.center) >= -sphere.radius)
+    }
+
+    /// Can report boxes just outside a corner as visible.
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            // The corner furthest along the normal.
+            let corner =
+                glam::Vec3::select(plane.normal.cmpge(glam::Vec3::ZERO), aabb.max, aabb.min);
+            plane.signed_distance(corner) >= 0.0
+        })
+    }
+
+    pub fn intersects(&self, bounds: &Bounds) -> bool {
+        match bounds {
+            Bounds::Point(point) => self.contains_point(*point),
+            Bounds::Sphere(sphere) => self.intersects_sphere(sphere),
+            Bounds::Aabb(aabb) => self.intersects_aabb(aabb),
+        }
+    }
+
+    /// The `objects` that may be visible, in order.
+    pub fn cull<'a, T: Bounded + 'a>(
+        &'a self,
+        objects: impl IntoIterator<Item = &'a T> + 'a,
+    ) -> impl Iterator<Item = &'a T> + 'a {
+        objects
+            .into_iter()
+            .filter(|object| self.intersects(&object.bounds()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+    use crate::resources::camera::{Camera, Projection};
+
+    const EPSILON: f32 = 1e-4;
+
+    /// At the origin looking down -Z, like view space.
+    fn camera(projection: Projection) -> Camera {
+        let mut camera = Camera::look_at(
+            glam::Vec3::ZERO,
+            glam::Vec3::NEG_Z,
+            2.0,
+            1.0,
+            1.0,
+            0.1,
+            10.0,
+        );
+        camera.set_projection(projection);
+        camera
+    }
+
+    fn assert_plane(plane: &Plane, normal: glam::Vec3, point: glam::Vec3) {
+        assert!(
+            plane.normal.abs_diff_eq(normal, EPSILON),
+            "{} != {normal}",
+            plane.normal
+        );
+        assert!(
+            plane.signed_distance(point).abs() < EPSILON,
+            "{point} isn't on {plane:?}"
+        );
+    }
+
+    #[test]
+    fn perspective_planes() {
+        // A 90 degree fov with an aspect of 2.
+        let camera = camera(Projection::Perspective {
+            fovy: FRAC_PI_2,
+            near: 0.1,
+            far: 10.0,
+        });
+        let frustum = camera.frustum();
+        let planes = frustum.planes();
+
+        assert_plane(
+            &planes[Frustum::LEFT],
+            glam::vec3(1.0, 0.0, -2.0).normalize(),
+            glam::vec3(-2.0, 0.0, -1.0),
+        );
+        assert_plane(
+            &planes[Frustum::RIGHT],
+            glam::vec3(-1.0, 0.0, -2.0).normalize(),
+            glam::vec3(2.0, 0.0, -1.0),
+        );
+        assert_plane(
+            &planes[Frustum::BOTTOM],
+            glam::vec3(0.0, 1.0, -1.0).normalize(),
+            glam::vec3(0.0, -1.0, -1.0),
+        );
+        assert_plane(
+            &planes[Frustum::TOP],
+            glam::vec3(0.0, -1.0, -1.0).normalize(),
+            glam::vec3(0.0, 1.0, -1.0),
+        );
+        assert_plane(
+            &planes[Frustum::DEPTH_ZERO],
+            glam::Vec3::NEG_Z,
+            glam::vec3(0.0, 0.0, -0.1),
+        );
+        assert_plane(
+            &planes[Frustum::DEPTH_ONE],
+            glam::Vec3::Z,
+            glam::vec3(0.0, 0.0, -10.0),
+        );
+
+        assert!(frustum.contains_point(glam::vec3(0.0, 0.0, -1.0)));
+        assert!(!frustum.contains_point(glam::vec3(0.0, 0.0, 1.0)));
+        assert!(!frustum.contains_point(glam::vec3(0.0, 0.0, -0.05)));
+        assert!(!frustum.contains_point(glam::vec3(0.0, 0.0, -11.0)));
+        assert!(!frustum.contains_point(glam::vec3(0.0, 1.5, -1.0)));
+        assert!(frustum.contains_point(glam::vec3(1.5, 0.0, -1.0)));
+    }
+
+    #[test]
+    fn reverse_infinite_has_no_far_plane() {
+        let camera = camera(Projection::ReverseInfinite {
+            fovy: FRAC_PI_2,
+            near: 0.1,
+        });
+        let frustum = camera.frustum();
+        let planes = frustum.planes();
+
+        assert_plane(
+            &planes[Frustum::DEPTH_ONE],
+            glam::Vec3::NEG_Z,
+            glam::vec3(0.0, 0.0, -0.1),
+        );
+        assert_eq!(planes[Frustum::DEPTH_ZERO].normal, glam::Vec3::ZERO);
+        assert!(frustum.contains_point(glam::vec3(0.0, 0.0, -1e6)));
+        assert!(!frustum.contains_point(glam::vec3(0.0, 0.0, -0.05)));
+    }
+
+    #[test]
+    fn orthographic_planes() {
+        // 4 high and, with an aspect of 2, 8 wide.
+        let camera = camera(Projection::Orthographic {
+            size: 4.0,
+            zoom: 1.0,
+            near: 0.1,
+            far: 10.0,
+        });
+        let frustum = camera.frustum();
+        let planes = frustum.planes();
+
+        assert_plane(
+            &planes[Frustum::LEFT],
+            glam::Vec3::X,
+            glam::vec3(-4.0, 0.0, -5.0),
+        );
+        assert_plane(
+            &planes[Frustum::RIGHT],
+            glam::Vec3::NEG_X,
+            glam::vec3(4.0, 0.0, -5.0),
+        );
+        assert_plane(
+            &planes[Frustum::BOTTOM],
+            glam::Vec3::Y,
+            glam::vec3(0.0, -2.0, -5.0),
+        );
+        assert_plane(
+            &planes[Frustum::TOP],
+            glam::Vec3::NEG_Y,
+            glam::vec3(0.0, 2.0, -5.0),
+        );
+        assert_plane(
+            &planes[Frustum::DEPTH_ZERO],
+            glam::Vec3::NEG_Z,
+            glam::vec3(0.0, 0.0, -0.1),
+        );
+        assert_plane(
+            &planes[Frustum::DEPTH_ONE],
+            glam::Vec3::Z,
+            glam::vec3(0.0, 0.0, -10.0),
+        );
+    }
+
+    #[test]
+    fn planes_follow_the_camera() {
+        let mut camera = camera(Projection::Perspective {
+            fovy: FRAC_PI_2,
+            near: 0.1,
+            far: 10.0,
+        });
+        camera.set_eye(glam::vec3(5.0, 0.0, 0.0));
+        camera.look_in(glam::Vec3::X);
+        let frustum = camera.frustum();
+        assert!(frustum.contains_point(glam::vec3(8.0, 0.0, 0.0)));
+        assert!(!frustum.contains_point(glam::vec3(2.0, 0.0, 0.0)));
+        assert_plane(
+            &frustum.planes()[Frustum::DEPTH_ZERO],
+            glam::Vec3::X,
+            glam::vec3(5.1, 0.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn spheres_and_boxes() {
+        let camera = camera(Projection::Orthographic {
+            size: 4.0,
+            zoom: 1.0,
+            near: 0.1,
+            far: 10.0,
+        });
+        let frustum = camera.frustum();
+
+        assert!(frustum.intersects_sphere(&Sphere::new(glam::vec3(0.0, 0.0, -5.0), 0.5)));
+        // Poking in from the top.
+        assert!(frustum.intersects_sphere(&Sphere::new(glam::vec3(0.0, 2.4, -5.0), 0.5)));
+        assert!(!frustum.intersects_sphere(&Sphere::new(glam::vec3(0.0, 2.6, -5.0), 0.5)));
+        // Behind the camera.
+        assert!(!frustum.intersects_sphere(&Sphere::new(glam::vec3(0.0, 0.0, 1.0), 0.5)));
+
+        let aabb = |min: glam::Vec3, max: glam::Vec3| Aabb::new(min, max);
+        assert!(frustum.intersects_aabb(&aabb(
+            glam::vec3(-1.0, -1.0, -6.0),
+            glam::vec3(1.0, 1.0, -4.0)
+        )));
+        // Bigger than the frustum.
+        assert!(frustum.intersects_aabb(&aabb(glam::Vec3::splat(-100.0), glam::Vec3::splat(100.0))));
+        assert!(frustum.intersects_aabb(&aabb(
+            glam::vec3(3.5, -1.0, -6.0),
+            glam::vec3(5.0, 1.0, -4.0)
+        )));
+        assert!(!frustum.intersects_aabb(&aabb(
+            glam::vec3(4.5, -1.0, -6.0),
+            glam::vec3(5.0, 1.0, -4.0)
+        )));
+        assert!(!frustum.intersects_aabb(&aabb(
+            glam::vec3(-1.0, -1.0, -20.0),
+            glam::vec3(1.0, 1.0, -11.0)
+        )));
+    }
+
+    #[test]
+    fn cull_keeps_visible_objects_in_order() {
+        struct Object(Bounds);
+
+        impl Bounded for Object {
+            fn bounds(&self) -> Bounds {
+                self.0
+            }
+        }
+
+        let camera = camera(Projection::Perspective {
+            fovy: FRAC_PI_2,
+            near: 0.1,
+            far: 10.0,
+        });
+        let frustum = camera.frustum();
+        let objects = [
+            Object(glam::vec3(0.0, 0.0, -1.0).into()),
+            Object(glam::vec3(0.0, 0.0, 1.0).into()),
+            Object(Sphere::new(glam::vec3(0.0, 0.0, -20.0), 1.0).into()),
+            Object(Aabb::new(glam::vec3(-1.0, -1.0, -3.0), glam::vec3(1.0, 1.0, -2.0)).into()),
+        ];
+        let visible: Vec<_> = frustum.cull(&objects).map(|object| object.0).collect();
+        assert_eq!(visible, [objects[0].0, objects[3].0]);
+    }
+}
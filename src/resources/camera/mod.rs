@@ -0,0 +1,677 @@
+pub mod fly;
+pub mod frustum;
+pub mod orbit;
+pub mod path;
+pub mod projection;
+pub mod viewport;
+
+pub use projection::Projection;
+pub use viewport::{PixelRect, Viewport};
+
+use std::{
+    f32::consts::{FRAC_PI_2, PI, TAU},
+    mem,
+};
+
+use bytemuck::{bytes_of, Zeroable};
+
+use super::ray::Ray;
+
+/// Just under straight up, so forward and world up never line up.
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.001;
+const MIN_PITCH: f32 = -MAX_PITCH;
+
+/// The camera uniform. Shaders get the matching struct by prepending
+/// [`CameraData::WGSL`] to their source, a test checks the two agree.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CameraData {
+    view_proj: glam::Mat4,
+    view: glam::Mat4,
+    proj: glam::Mat4,
+    inv_view_proj: glam::Mat4,
+    inv_view: glam::Mat4,
+    inv_proj: glam::Mat4,
+    eye: glam::Vec3,
+    /// Seconds since the demo started.
+    time: f32,
+    /// In physical pixels.
+    viewport: glam::Vec2,
+    near: f32,
+    /// 0 for [`Projection::ReverseInfinite`], as shaders can't be trusted
+    /// with infinities. No finite projection has its far plane at 0.
+    far: f32,
+}
+
+impl CameraData {
+    pub const WGSL: &'static str = "\
+struct CameraData {
+    view_proj: mat4x4<f32>,
+    view: mat4x4<f32>,
+    proj: mat4x4<f32>,
+    inv_view_proj: mat4x4<f32>,
+    inv_view: mat4x4<f32>,
+    inv_proj: mat4x4<f32>,
+    eye: vec3<f32>,
+    time: f32,
+    viewport: vec2<f32>,
+    near: f32,
+    // 0 if there is no far plane.
+    far: f32,
+}
+";
+
+    pub fn new(camera: &Camera, time: f32) -> Self {
+        let view = camera.calc_view();
+        let proj = camera.calc_proj();
+        let view_proj = proj * view;
+        Self {
+            view_proj,
+            view,
+            proj,
+            inv_view_proj: view_proj.inverse(),
+            inv_view: view.inverse(),
+            inv_proj: proj.inverse(),
+            eye: camera.eye(),
+            time,
+            viewport: camera.viewport(),
+            near: camera.near(),
+            far: if camera.far().is_finite() { camera.far() } else { 0.0 },
+        }
+    }
+
+    /// `source` with the camera struct in front of it.
+    pub fn with_wgsl(source: &str) -> String {
+        format!("{}\n{source}", Self::WGSL)
+    }
+}
+
+/// Creates [`CameraBinding`]s. The uniform is bound with a dynamic offset,
+/// so one buffer holds the data of several cameras.
+pub struct CameraBinder {
+    layout: wgpu::BindGroupLayout,
+    /// Size of a slot, [`CameraData`] padded to the offset alignment.
+    stride: u64,
+}
+
+impl CameraBinder {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("CameraBinder"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: true,
+                    min_binding_size: wgpu::BufferSize::new(mem::size_of::<CameraData>() as u64),
+                },
+                count: None,
+            }],
+        });
+        let alignment = device.limits().min_uniform_buffer_offset_alignment as u64;
+        let stride = (mem::size_of::<CameraData>() as u64).next_multiple_of(alignment);
+        Self { layout, stride }
+    }
+
+    /// A binding with room for `slots` cameras, see [`CameraBinding::update`].
+    pub fn bind(&self, device: &wgpu::Device, slots: usize) -> CameraBinding {
+        let slots = slots.max(1);
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("CameraBinding::buffer"),
+            size: self.stride * slots as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("CameraBinding::bind_group"),
+            layout: &self.layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: &buffer,
+                    offset: 0,
+                    size: wgpu::BufferSize::new(mem::size_of::<CameraData>() as u64),
+                }),
+            }],
+        });
+        CameraBinding {
+            bind_group,
+            buffer,
+            stride: self.stride,
+            data: vec![CameraData::zeroed(); slots],
+        }
+    }
+
+    pub fn layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+}
+
+pub struct CameraBinding {
+    bind_group: wgpu::BindGroup,
+    buffer: wgpu::Buffer,
+    stride: u64,
+    data: Vec<CameraData>,
+}
+
+impl CameraBinding {
+    /// Panics if `slot` is out of range.
+    pub fn update(&mut self, queue: &wgpu::Queue, slot: usize, camera: &Camera, time: f32) {
+        self.data[slot] = CameraData::new(camera, time);
+        queue.write_buffer(&self.buffer, self.stride * slot as u64, bytes_of(&self.data[slot]));
+    }
+
+    /// Binds the camera in `slot` to group `index` of `pass`.
+    pub fn set<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>, index: u32, slot: usize) {
+        pass.set_bind_group(index, &self.bind_group, &[self.offset(slot)]);
+    }
+
+    /// The dynamic offset of `slot`.
+    pub fn offset(&self, slot: usize) -> u32 {
+        (self.stride * slot as u64) as u32
+    }
+
+    pub fn slots(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    pub fn data(&self, slot: usize) -> &CameraData {
+        &self.data[slot]
+    }
+}
+
+/// A camera looking through a [`Projection`]. The orientation is stored as
+/// a quaternion, yaw, pitch and roll are derived from it. A yaw of 0 looks
+/// along +X and positive yaw turns right, towards +Z.
+#[derive(Debug)]
+pub struct Camera {
+    viewport: glam::Vec2,
+    projection: Projection,
+    eye: glam::Vec3,
+    orientation: glam::Quat,
+}
+
+impl Camera {
+    pub fn look_at(eye: glam::Vec3, position: glam::Vec3, width: f32, height: f32, fovy: f32, near: f32, far: f32) -> Self {
+        let mut camera = Self {
+            eye,
+            orientation: glam::Quat::IDENTITY,
+            viewport: glam::vec2(width, height),
+            projection: Projection::Perspective { fovy, near, far },
+        };
+        camera.look_in(position - eye);
+        camera
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.viewport = glam::vec2(width as f32, height as f32);
+    }
+
+    /// Turns around world up, so the horizon stays where it is.
+    pub fn rotate_right(&mut self, amount: f32) {
+        self.rotate(glam::Quat::from_rotation_y(-amount));
+    }
+
+    /// Turns around the horizontal right axis, which is
+    /// [`Camera::right`] without the roll.
+    pub fn rotate_up(&mut self, amount: f32) {
+        let pitch = self.pitch();
+        let amount = (pitch + amount).clamp(MIN_PITCH, MAX_PITCH) - pitch;
+        let axis = self.ground_right();
+        self.rotate(glam::Quat::from_axis_angle(axis, amount));
+    }
+
+    /// Positive roll tilts the camera clockwise, as seen from behind it.
+    pub fn roll_right(&mut self, amount: f32) {
+        self.orientation = (self.orientation * glam::Quat::from_rotation_z(-amount)).normalize();
+    }
+
+    /// Moves along the ground, ignoring pitch and roll.
+    pub fn walk_forward(&mut self, amount: f32) {
+        self.eye += self.ground_forward() * amount;
+    }
+
+    /// Moves along the ground, ignoring pitch and roll.
+    pub fn walk_right(&mut self, amount: f32) {
+        self.eye += self.ground_right() * amount;
+    }
+
+    pub fn levitate_up(&mut self, amount: f32) {
+        self.eye.y += amount;
+    }
+
+    /// Points the camera along `direction`, keeping the roll.
+    pub fn look_in(&mut self, direction: glam::Vec3) {
+        let forward = direction.normalize();
+        let pitch = forward.y.clamp(-1.0, 1.0).asin();
+        let yaw = forward.z.atan2(forward.x);
+        self.orientation = from_angles(yaw, pitch, self.roll());
+    }
+
+    pub fn set_eye(&mut self, eye: glam::Vec3) {
+        self.eye = eye;
+    }
+
+    /// Pitch is clamped like everywhere else, so the result can differ from
+    /// `orientation` if it looks straight up or down.
+    pub fn set_orientation(&mut self, orientation: glam::Quat) {
+        self.orientation = orientation.normalize();
+        let (yaw, pitch, roll) = self.angles();
+        if !(MIN_PITCH..=MAX_PITCH).contains(&pitch) {
+            self.orientation = from_angles(yaw, pitch, roll);
+        }
+    }
+
+    pub fn calc_view(&self) -> glam::Mat4 {
+        glam::Mat4::from_quat(self.orientation.conjugate()) * glam::Mat4::from_translation(-self.eye)
+    }
+
+    pub fn calc_proj(&self) -> glam::Mat4 {
+        self.projection.matrix(self.aspect())
+    }
+
+    pub fn frustum(&self) -> frustum::Frustum {
+        frustum::Frustum::from_matrix(self.calc_proj() * self.calc_view())
+    }
+
+    /// The ray through a cursor position in physical pixels, as
+    /// `Demo::on_cursor_moved` gets them. It starts on the near plane.
+    pub fn cursor_ray(&self, x: f64, y: f64) -> Ray {
+        let ndc = glam::vec2(
+            2.0 * x as f32 / self.viewport.x.max(1.0) - 1.0,
+            1.0 - 2.0 * y as f32 / self.viewport.y.max(1.0),
+        );
+        let near_depth = if self.projection.is_reverse_z() { 1.0 } else { 0.0 };
+        // Halfway is finite even for infinite projections.
+        let inv_view_proj = (self.calc_proj() * self.calc_view()).inverse();
+        let near = inv_view_proj.project_point3(ndc.extend(near_depth));
+        let mid = inv_view_proj.project_point3(ndc.extend(0.5));
+        Ray::new(near, (mid - near).normalize())
+    }
+
+    pub fn projection(&self) -> &Projection {
+        &self.projection
+    }
+
+    pub fn projection_mut(&mut self) -> &mut Projection {
+        &mut self.projection
+    }
+
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
+    }
+
+    /// In radians from -PI to PI.
+    pub fn yaw(&self) -> f32 {
+        self.angles().0
+    }
+
+    pub fn pitch(&self) -> f32 {
+        self.angles().1
+    }
+
+    pub fn roll(&self) -> f32 {
+        self.angles().2
+    }
+
+    /// Rotates the camera's local axes into the world. Locally the camera
+    /// looks along -Z with +Y up, like [`glam::Mat4::look_to_rh`].
+    pub fn orientation(&self) -> glam::Quat {
+        self.orientation
+    }
+
+    pub fn eye(&self) -> glam::Vec3 {
+        self.eye
+    }
+
+    pub fn forward(&self) -> glam::Vec3 {
+        self.orientation * glam::Vec3::NEG_Z
+    }
+
+    pub fn right(&self) -> glam::Vec3 {
+        self.orientation * glam::Vec3::X
+    }
+
+    pub fn up(&self) -> glam::Vec3 {
+        self.orientation * glam::Vec3::Y
+    }
+
+    /// `None` for orthographic projections.
+    pub fn fovy(&self) -> Option<f32> {
+        self.projection.fovy()
+    }
+
+    pub fn aspect(&self) -> f32 {
+        self.viewport.x / self.viewport.y.max(1.0)
+    }
+
+    /// In physical pixels, as last passed to [`Camera::resize`].
+    pub fn viewport(&self) -> glam::Vec2 {
+        self.viewport
+    }
+
+    pub fn near(&self) -> f32 {
+        self.projection.near()
+    }
+
+    pub fn far(&self) -> f32 {
+        self.projection.far()
+    }
+
+    fn rotate(&mut self, rotation: glam::Quat) {
+        self.orientation = (rotation * self.orientation).normalize();
+    }
+
+    /// Forward flattened onto the ground. Never zero, as the pitch stops
+    /// short of vertical.
+    fn ground_forward(&self) -> glam::Vec3 {
+        let (syaw, cyaw) = self.yaw().sin_cos();
+        glam::vec3(cyaw, 0.0, syaw)
+    }
+
+    fn ground_right(&self) -> glam::Vec3 {
+        let forward = self.ground_forward();
+        glam::vec3(-forward.z, 0.0, forward.x)
+    }
+
+    fn angles(&self) -> (f32, f32, f32) {
+        let (yaw, pitch, roll) = self.orientation.to_euler(glam::EulerRot::YXZ);
+        let mut yaw = -yaw - FRAC_PI_2;
+        if yaw <= -PI {
+            yaw += TAU;
+        }
+        (yaw, pitch, -roll)
+    }
+}
+
+/// The inverse of [`Camera::angles`], with the pitch clamped.
+fn from_angles(yaw: f32, pitch: f32, roll: f32) -> glam::Quat {
+    // A quarter turn takes -Z to +X, where yaw 0 looks.
+    glam::Quat::from_euler(
+        glam::EulerRot::YXZ,
+        -yaw - FRAC_PI_2,
+        pitch.clamp(MIN_PITCH, MAX_PITCH),
+        -roll,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_4, PI};
+
+    use super::*;
+
+    const EPSILON: f32 = 1e-5;
+
+    fn camera() -> Camera {
+        Camera::look_at(glam::vec3(1.0, 2.0, 3.0), glam::Vec3::ZERO, 16.0, 9.0, FRAC_PI_4, 0.1, 100.0)
+    }
+
+    fn assert_near(a: glam::Vec3, b: glam::Vec3) {
+        assert!(a.abs_diff_eq(b, EPSILON), "{a} != {b}");
+    }
+
+    fn assert_orthonormal(camera: &Camera) {
+        let (forward, right, up) = (camera.forward(), camera.right(), camera.up());
+        for v in [forward, right, up] {
+            assert!((v.length() - 1.0).abs() < EPSILON, "{v} isn't normalized");
+        }
+        assert!(forward.dot(right).abs() < EPSILON);
+        assert!(forward.dot(up).abs() < EPSILON);
+        assert!(right.dot(up).abs() < EPSILON);
+        // Right handed
+        assert_near(right.cross(forward), up);
+    }
+
+    #[test]
+    fn yaw_zero_looks_along_x() {
+        let mut camera = camera();
+        camera.set_orientation(glam::Quat::from_rotation_y(-FRAC_PI_2));
+        assert!(camera.yaw().abs() < EPSILON);
+        assert_near(camera.forward(), glam::Vec3::X);
+        assert_near(camera.right(), glam::Vec3::Z);
+        assert_near(camera.up(), glam::Vec3::Y);
+    }
+
+    #[test]
+    fn look_at_points_forward_at_the_target() {
+        let camera = camera();
+        assert_near(camera.forward(), (glam::Vec3::ZERO - camera.eye()).normalize());
+        assert!(camera.pitch() < 0.0);
+        assert_orthonormal(&camera);
+        assert!(camera.up().y > 0.0);
+        assert!(camera.right().y.abs() < EPSILON);
+    }
+
+    #[test]
+    fn basis_stays_orthonormal() {
+        let mut camera = camera();
+        for _ in 0..100 {
+            camera.rotate_right(0.37);
+            camera.rotate_up(0.11);
+            camera.roll_right(0.05);
+            assert_orthonormal(&camera);
+        }
+    }
+
+    #[test]
+    fn positive_yaw_turns_right() {
+        let mut camera = camera();
+        let right = camera.right();
+        let forward = camera.forward();
+        camera.rotate_right(0.1);
+        assert!(camera.forward().dot(right) > 0.0);
+        assert!(camera.forward().dot(forward) > 0.99);
+    }
+
+    #[test]
+    fn pitch_stops_short_of_vertical() {
+        let mut camera = camera();
+        camera.rotate_up(PI);
+        assert!(camera.pitch() < FRAC_PI_2);
+        assert!(camera.forward().y < 1.0);
+        // Still upright, not flipped over the top.
+        assert!(camera.up().y > 0.0);
+        assert_orthonormal(&camera);
+
+        camera.rotate_up(-2.0 * PI);
+        assert!(camera.pitch() > -FRAC_PI_2);
+        assert!(camera.up().y > 0.0);
+        assert_orthonormal(&camera);
+    }
+
+    #[test]
+    fn roll_turns_up_around_forward() {
+        let mut camera = camera();
+        let (forward, right, up) = (camera.forward(), camera.right(), camera.up());
+        camera.roll_right(FRAC_PI_2);
+        assert_near(camera.forward(), forward);
+        assert_near(camera.up(), right);
+        assert_near(camera.right(), -up);
+    }
+
+    #[test]
+    fn angles_are_derived_from_the_orientation() {
+        let mut camera = camera();
+        camera.set_orientation(glam::Quat::from_rotation_y(-FRAC_PI_2));
+        camera.rotate_right(0.5);
+        camera.rotate_up(0.25);
+        camera.roll_right(0.125);
+        // Each turn only changes its own angle, even with roll.
+        camera.rotate_up(0.25);
+        camera.rotate_right(0.5);
+        assert!((camera.yaw() - 1.0).abs() < EPSILON);
+        assert!((camera.pitch() - 0.5).abs() < EPSILON);
+        assert!((camera.roll() - 0.125).abs() < EPSILON);
+
+        // Yaw wraps around instead of growing forever.
+        camera.rotate_right(2.0 * PI + 0.5);
+        assert!((camera.yaw() - 1.5).abs() < 1e-4);
+        camera.rotate_right(2.0);
+        assert!((camera.yaw() - (3.5 - 2.0 * PI)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn set_orientation_clamps_the_pitch() {
+        let mut camera = camera();
+        camera.set_orientation(glam::Quat::from_rotation_x(FRAC_PI_2));
+        assert!((camera.pitch() - MAX_PITCH).abs() < 1e-3);
+        assert!(camera.forward().y < 1.0);
+        assert_orthonormal(&camera);
+    }
+
+    #[test]
+    fn set_orientation_round_trips() {
+        let mut camera = camera();
+        camera.rotate_right(1.0);
+        camera.rotate_up(0.5);
+        camera.roll_right(0.25);
+        let orientation = camera.orientation();
+
+        let mut other = Camera::look_at(glam::Vec3::ZERO, glam::Vec3::X, 1.0, 1.0, 1.0, 0.1, 10.0);
+        other.set_orientation(orientation);
+        assert!(other.orientation().abs_diff_eq(orientation, EPSILON) || other.orientation().abs_diff_eq(-orientation, EPSILON));
+    }
+
+    #[test]
+    fn view_moves_eye_to_origin_looking_down_neg_z() {
+        let camera = camera();
+        let view = camera.calc_view();
+
+        assert_near(view.transform_point3(camera.eye()), glam::Vec3::ZERO);
+
+        let distance = camera.eye().length();
+        assert_near(view.transform_point3(glam::Vec3::ZERO), glam::vec3(0.0, 0.0, -distance));
+        assert_near(view.transform_vector3(camera.right()), glam::Vec3::X);
+        assert_near(view.transform_vector3(camera.up()), glam::Vec3::Y);
+    }
+
+    #[test]
+    fn reverse_infinite_maps_near_to_one() {
+        let mut camera = camera();
+        camera.set_projection(Projection::ReverseInfinite { fovy: FRAC_PI_4, near: 0.1 });
+        let proj = camera.calc_proj();
+        let near = proj.project_point3(glam::vec3(0.0, 0.0, -0.1));
+        let far = proj.project_point3(glam::vec3(0.0, 0.0, -1e6));
+        assert!((near.z - 1.0).abs() < EPSILON);
+        assert!(far.z > 0.0 && far.z < 1e-5);
+        assert_eq!(camera.projection().depth_clear(), 0.0);
+        assert_eq!(camera.projection().depth_compare(), wgpu::CompareFunction::Greater);
+    }
+
+    #[test]
+    fn orthographic_follows_zoom_and_aspect() {
+        let mut camera = camera();
+        camera.set_projection(Projection::Orthographic { size: 4.0, zoom: 1.0, near: 0.1, far: 100.0 });
+        camera.resize(200, 100);
+        let corner = glam::vec3(4.0, 2.0, -50.0);
+        assert_near(camera.calc_proj().project_point3(corner).truncate().extend(0.0), glam::vec3(1.0, 1.0, 0.0));
+
+        if let Projection::Orthographic { zoom, .. } = camera.projection_mut() {
+            *zoom = 2.0;
+        }
+        assert_near(camera.calc_proj().project_point3(corner).truncate().extend(0.0), glam::vec3(2.0, 2.0, 0.0));
+        assert!(camera.fovy().is_none());
+    }
+
+    #[test]
+    fn wgsl_matches_camera_data() {
+        let module = naga::front::wgsl::parse_str(CameraData::WGSL).unwrap();
+        let mut layouter = naga::proc::Layouter::default();
+        layouter.update(module.to_ctx()).unwrap();
+
+        let (handle, ty) = module
+            .types
+            .iter()
+            .find(|(_, ty)| ty.name.as_deref() == Some("CameraData"))
+            .unwrap();
+        let naga::TypeInner::Struct { members, span } = &ty.inner else {
+            panic!("CameraData isn't a struct");
+        };
+
+        let expected = [
+            ("view_proj", std::mem::offset_of!(CameraData, view_proj)),
+            ("view", std::mem::offset_of!(CameraData, view)),
+            ("proj", std::mem::offset_of!(CameraData, proj)),
+            ("inv_view_proj", std::mem::offset_of!(CameraData, inv_view_proj)),
+            ("inv_view", std::mem::offset_of!(CameraData, inv_view)),
+            ("inv_proj", std::mem::offset_of!(CameraData, inv_proj)),
+            ("eye", std::mem::offset_of!(CameraData, eye)),
+            ("time", std::mem::offset_of!(CameraData, time)),
+            ("viewport", std::mem::offset_of!(CameraData, viewport)),
+            ("near", std::mem::offset_of!(CameraData, near)),
+            ("far", std::mem::offset_of!(CameraData, far)),
+        ];
+        let actual: Vec<_> = members
+            .iter()
+            .map(|member| (member.name.as_deref().unwrap(), member.offset as usize))
+            .collect();
+        assert_eq!(actual, expected);
+        assert_eq!(*span as usize, std::mem::size_of::<CameraData>());
+        assert_eq!(layouter[handle].size as usize, std::mem::size_of::<CameraData>());
+    }
+
+    #[test]
+    fn camera_data_inverses() {
+        let camera = camera();
+        let data = CameraData::new(&camera, 0.0);
+        assert!((data.view_proj * data.inv_view_proj).abs_diff_eq(glam::Mat4::IDENTITY, 1e-4));
+        assert_near(data.inv_view.transform_point3(glam::Vec3::ZERO), camera.eye());
+        assert_eq!(data.viewport, glam::vec2(16.0, 9.0));
+    }
+
+    #[test]
+    fn camera_data_has_no_infinite_far_plane() {
+        let mut camera = camera();
+        assert_eq!(CameraData::new(&camera, 0.0).far, 100.0);
+        camera.set_projection(Projection::ReverseInfinite { fovy: FRAC_PI_4, near: 0.1 });
+        let data = CameraData::new(&camera, 0.0);
+        assert_eq!(data.far, 0.0);
+        assert!(bytemuck::cast_slice::<_, f32>(bytes_of(&data)).iter().all(|x| x.is_finite()));
+    }
+
+    #[test]
+    fn cursor_ray_goes_through_the_pixel() {
+        let mut camera = camera();
+        camera.resize(160, 90);
+        let center = camera.cursor_ray(80.0, 45.0);
+        assert_near(center.direction, camera.forward());
+        assert!((center.origin.distance(camera.eye()) - 0.1).abs() < 1e-4);
+
+        // The top left corner is up and to the left.
+        let corner = camera.cursor_ray(0.0, 0.0);
+        assert!(corner.direction.dot(camera.up()) > 0.0);
+        assert!(corner.direction.dot(camera.right()) < 0.0);
+        let clip = camera.calc_proj() * camera.calc_view();
+        let ndc = clip.project_point3(corner.at(5.0));
+        assert!(ndc.truncate().abs_diff_eq(glam::vec2(-1.0, 1.0), 1e-4));
+
+        camera.set_projection(Projection::ReverseInfinite { fovy: FRAC_PI_4, near: 0.1 });
+        assert_near(camera.cursor_ray(80.0, 45.0).direction, camera.forward());
+
+        camera.set_projection(Projection::Orthographic { size: 2.0, zoom: 1.0, near: 0.1, far: 100.0 });
+        let corner = camera.cursor_ray(0.0, 0.0);
+        assert_near(corner.direction, camera.forward());
+        let offset = corner.origin - camera.eye();
+        assert!((offset.dot(camera.up()) - 1.0).abs() < 1e-4);
+        assert!((offset.dot(camera.right()) + 16.0 / 9.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn view_matches_look_to() {
+        let mut camera = camera();
+        camera.rotate_right(0.3);
+        camera.rotate_up(-0.2);
+        let expected = glam::Mat4::look_to_rh(camera.eye(), camera.forward(), camera.up());
+        assert!(camera.calc_view().abs_diff_eq(expected, EPSILON));
+    }
+}
@@ -0,0 +1,368 @@
+use std::f32::consts::FRAC_PI_2;
+
+use crate::input::{self, InputMap};
+
+use super::{Camera, Projection};
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct OrbitSettings {
+    /// Multiplies the `look_*` axes while dragging.
+    pub rotate_speed: f32,
+    /// Fraction of the distance to the target moved per unit of the
+    /// `look_*` axes while panning.
+    pub pan_speed: f32,
+    /// How much one unit of [`input::ORBIT_ZOOM`] changes the distance,
+    /// relative to the current distance.
+    pub zoom_speed: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    /// In radians, positive looks down on the target.
+    pub min_pitch: f32,
+    pub max_pitch: f32,
+    /// How quickly the camera catches up with the input. 0 disables
+    /// smoothing.
+    pub damping: f32,
+}
+
+impl Default for OrbitSettings {
+    fn default() -> Self {
+        Self {
+            rotate_speed: 1.0,
+            pan_speed: 1.0,
+            zoom_speed: 0.2,
+            min_distance: 0.1,
+            max_distance: 100.0,
+            min_pitch: -FRAC_PI_2 + 0.01,
+            max_pitch: FRAC_PI_2 - 0.01,
+            damping: 15.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Orbit {
+    target: glam::Vec3,
+    distance: f32,
+    yaw: f32,
+    pitch: f32,
+}
+
+impl Orbit {
+    fn offset(&self) -> glam::Vec3 {
+        let (syaw, cyaw) = self.yaw.sin_cos();
+        let (spitch, cpitch) = self.pitch.sin_cos();
+        glam::vec3(cyaw * cpitch, spitch, syaw * cpitch) * self.distance
+    }
+
+    fn lerp(&self, goal: &Self, t: f32) -> Self {
+        Self {
+            target: self.target.lerp(goal.target, t),
+            distance: self.distance + (goal.distance - self.distance) * t,
+            yaw: self.yaw + (goal.yaw - self.yaw) * t,
+            pitch: self.pitch + (goal.pitch - self.pitch) * t,
+        }
+    }
+}
+
+/// Orbits a [`Camera`] around a target point. Dragging with
+/// [`input::ORBIT_ROTATE`] held rotates, [`input::ORBIT_PAN`] moves the
+/// target and [`input::ORBIT_ZOOM`] changes the distance.
+#[derive(Debug)]
+pub struct OrbitController {
+    pub settings: OrbitSettings,
+    current: Orbit,
+    goal: Orbit,
+}
+
+impl Default for OrbitController {
+    fn default() -> Self {
+        Self::new(OrbitSettings::default())
+    }
+}
+
+impl OrbitController {
+    pub fn new(settings: OrbitSettings) -> Self {
+        let orbit = Orbit {
+            target: glam::Vec3::ZERO,
+            distance: 5.0,
+            yaw: 0.0,
+            pitch: 0.0,
+        };
+        Self {
+            settings,
+            current: orbit,
+            goal: orbit,
+        }
+    }
+
+    pub fn target(&self) -> glam::Vec3 {
+        self.goal.target
+    }
+
+    pub fn distance(&self) -> f32 {
+        self.goal.distance
+    }
+
+    pub fn set_target(&mut self, target: glam::Vec3) {
+        self.goal.target = target;
+    }
+
+    /// Orbits `target` from where `eye` is. Jumps there without smoothing.
+    pub fn look_from(&mut self, eye: glam::Vec3, target: glam::Vec3) {
+        let offset = eye - target;
+        let distance = offset.length();
+        self.goal = Orbit {
+            target,
+            distance,
+            yaw: offset.z.atan2(offset.x),
+            pitch: (offset.y / distance.max(f32::EPSILON)).asin(),
+        };
+        self.clamp_goal();
+        self.current = self.goal;
+    }
+
+    /// Moves the target to the center of the box and backs off until all
+    /// of it is in view of `camera`. Orthographic cameras are zoomed
+    /// instead, as the distance doesn't change what they see.
+    pub fn frame_bounds(&mut self, min: glam::Vec3, max: glam::Vec3, camera: &mut Camera) {
+        let center = (min + max) * 0.5;
+        let radius = (max - min).length() * 0.5;
+        let aspect = camera.aspect();
+
+        self.goal.target = center;
+        match camera.projection_mut() {
+            Projection::Orthographic { size, zoom, near, .. } => {
+                *zoom = *size * 0.5 * aspect.min(1.0) / radius.max(f32::EPSILON);
+                self.goal.distance = *near + radius;
+            }
+            projection => {
+                let half_fovy = projection.fovy().unwrap_or_default() * 0.5;
+                let half_fovx = (half_fovy.tan() * aspect).atan();
+                let half_fov = half_fovy.min(half_fovx);
+                self.goal.distance =
+                    (radius / half_fov.sin()).max(projection.near() + radius);
+            }
+        }
+        self.clamp_goal();
+    }
+
+    pub fn update(&mut self, camera: &mut Camera, input: &InputMap, dt: f32) {
+        let look = glam::vec2(input.value(input::LOOK_X), input.value(input::LOOK_Y));
+
+        if input.pressed(input::ORBIT_ROTATE) {
+            // Dragging right moves the camera left around the target, the
+            // target appears to turn right.
+            self.goal.yaw += look.x * self.settings.rotate_speed;
+            self.goal.pitch -= look.y * self.settings.rotate_speed;
+        }
+
+        if input.pressed(input::ORBIT_PAN) {
+            let scale = self.goal.distance * self.settings.pan_speed;
+            let right = camera.right().normalize_or_zero();
+            let up = camera.up().normalize_or_zero();
+            self.goal.target -= (right * look.x + up * look.y) * scale;
+        }
+
+        let zoom = input.value(input::ORBIT_ZOOM);
+        if zoom != 0.0 {
+            let factor = (-zoom * self.settings.zoom_speed).exp();
+            match camera.projection_mut() {
+                Projection::Orthographic { zoom, .. } => *zoom /= factor,
+                _ => self.goal.distance *= factor,
+            }
+        }
+
+        self.clamp_goal();
+
+        self.current = if self.settings.damping > 0.0 {
+            let t = 1.0 - (-self.settings.damping * dt).exp();
+            self.current.lerp(&self.goal, t)
+        } else {
+            self.goal
+        };
+
+        self.apply(camera);
+    }
+
+    /// Moves `camera` to the current position on the orbit.
+    pub fn apply(&self, camera: &mut Camera) {
+        let offset = self.current.offset();
+        camera.set_eye(self.current.target + offset);
+        camera.look_in(-offset);
+    }
+
+    fn clamp_goal(&mut self) {
+        let settings = &self.settings;
+        self.goal.distance = self
+            .goal
+            .distance
+            .clamp(settings.min_distance, settings.max_distance);
+        self.goal.pitch = self
+            .goal
+            .pitch
+            .clamp(settings.min_pitch, settings.max_pitch);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
+    use winit::event::MouseButton;
+
+    use super::*;
+    use crate::input::{Bindings, Button};
+
+    fn camera() -> Camera {
+        Camera::look_at(
+            glam::vec3(5.0, 0.0, 0.0),
+            glam::Vec3::ZERO,
+            16.0,
+            9.0,
+            FRAC_PI_4,
+            0.1,
+            1000.0,
+        )
+    }
+
+    /// Without smoothing, so every update lands on the goal.
+    fn controller() -> OrbitController {
+        OrbitController::new(OrbitSettings {
+            damping: 0.0,
+            ..Default::default()
+        })
+    }
+
+    fn drag(controller: &mut OrbitController, camera: &mut Camera, dx: f64, dy: f64) {
+        let mut input = InputMap::new(Bindings::default());
+        input.on_button(Button::Mouse(MouseButton::Left), true);
+        input.on_motion(0, dx);
+        input.on_motion(1, dy);
+        controller.update(camera, &input, 0.01);
+    }
+
+    fn scroll(controller: &mut OrbitController, camera: &mut Camera, y: f32) {
+        let mut input = InputMap::new(Bindings::default());
+        input.on_scroll(0.0, y);
+        controller.update(camera, &input, 0.01);
+    }
+
+    #[test]
+    fn looks_at_the_target_from_the_distance() {
+        let (mut controller, mut camera) = (controller(), camera());
+        controller.look_from(glam::vec3(1.0, 2.0, 3.0), glam::vec3(1.0, 0.0, 0.0));
+        controller.apply(&mut camera);
+        assert!(camera.eye().abs_diff_eq(glam::vec3(1.0, 2.0, 3.0), 1e-5));
+        assert!((controller.distance() - 13f32.sqrt()).abs() < 1e-5);
+        let to_target = (controller.target() - camera.eye()).normalize();
+        assert!(camera.forward().abs_diff_eq(to_target, 1e-5));
+    }
+
+    #[test]
+    fn pitch_is_clamped() {
+        let settings = OrbitSettings::default();
+        let (mut controller, mut camera) = (controller(), camera());
+        for dy in [10_000.0, -10_000.0] {
+            drag(&mut controller, &mut camera, 0.0, dy);
+            let pitch = (camera.eye().y / controller.distance()).asin();
+            let limit = if dy > 0.0 {
+                settings.max_pitch
+            } else {
+                settings.min_pitch
+            };
+            assert!((pitch - limit).abs() < 1e-3, "{pitch} != {limit}");
+            // Still looking at the target, not flipped over.
+            assert!(camera
+                .forward()
+                .abs_diff_eq(-camera.eye().normalize(), 1e-4));
+            assert!(camera.up().y > 0.0);
+        }
+    }
+
+    #[test]
+    fn dragging_keeps_the_distance() {
+        let (mut controller, mut camera) = (controller(), camera());
+        drag(&mut controller, &mut camera, 50.0, 20.0);
+        assert!((camera.eye().length() - 5.0).abs() < 1e-4);
+        assert!(camera.eye().z != 0.0 && camera.eye().y != 0.0);
+    }
+
+    #[test]
+    fn zoom_stays_within_limits() {
+        let settings = OrbitSettings::default();
+        let (mut controller, mut camera) = (controller(), camera());
+        scroll(&mut controller, &mut camera, 1.0);
+        assert!((controller.distance() - 5.0 * (-settings.zoom_speed).exp()).abs() < 1e-5);
+
+        scroll(&mut controller, &mut camera, 1000.0);
+        assert_eq!(controller.distance(), settings.min_distance);
+        scroll(&mut controller, &mut camera, -1000.0);
+        assert_eq!(controller.distance(), settings.max_distance);
+        assert!((camera.eye().length() - settings.max_distance).abs() < 1e-3);
+    }
+
+    #[test]
+    fn orthographic_zoom_changes_the_zoom() {
+        let (mut controller, mut camera) = (controller(), camera());
+        camera.set_projection(Projection::Orthographic {
+            size: 4.0,
+            zoom: 1.0,
+            near: 0.1,
+            far: 100.0,
+        });
+        scroll(&mut controller, &mut camera, 1.0);
+        assert_eq!(controller.distance(), 5.0);
+        let Projection::Orthographic { zoom, .. } = *camera.projection() else {
+            unreachable!();
+        };
+        assert!(zoom > 1.0);
+    }
+
+    #[test]
+    fn framed_bounds_are_in_view() {
+        let (min, max) = (glam::vec3(-3.0, -1.0, 2.0), glam::vec3(5.0, 4.0, 3.0));
+        let corners = (0..8).map(|i| {
+            glam::vec3(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            )
+        });
+
+        for projection in [
+            Projection::Perspective {
+                fovy: FRAC_PI_4,
+                near: 0.1,
+                far: 1000.0,
+            },
+            Projection::ReverseInfinite {
+                fovy: FRAC_PI_4,
+                near: 0.1,
+            },
+            Projection::Orthographic {
+                size: 2.0,
+                zoom: 1.0,
+                near: 0.1,
+                far: 1000.0,
+            },
+        ] {
+            for (width, height) in [(1600, 900), (900, 1600)] {
+                let (mut controller, mut camera) = (controller(), camera());
+                camera.set_projection(projection);
+                camera.resize(width, height);
+                controller.frame_bounds(min, max, &mut camera);
+                controller.update(&mut camera, &InputMap::default(), 0.01);
+
+                assert_eq!(controller.target(), (min + max) * 0.5);
+                let frustum = camera.frustum();
+                for corner in corners.clone() {
+                    assert!(
+                        frustum.contains_point(corner),
+                        "{projection:?} {width}x{height} {corner}"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use crate::resources::fs::{load_json, save_json};
+
+use super::{Camera, Projection};
+
+/// What a [`CameraPath`] does once it reaches the last keyframe.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum PlayMode {
+    /// Stays on the last keyframe.
+    Once,
+    /// Jumps back to the first keyframe.
+    #[default]
+    Loop,
+    /// Plays backwards to the first keyframe, then forwards again.
+    PingPong,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Keyframe {
+    /// In seconds.
+    pub time: f32,
+    pub eye: glam::Vec3,
+    pub orientation: glam::Quat,
+    /// `None` for orthographic cameras, which keep their projection.
+    pub fovy: Option<f32>,
+}
+
+impl Keyframe {
+    pub fn from_camera(camera: &Camera, time: f32) -> Self {
+        Self {
+            time,
+            eye: camera.eye(),
+            orientation: camera.orientation(),
+            fovy: camera.fovy(),
+        }
+    }
+
+    /// Moves `camera` to the keyframe. The fov is only applied to
+    /// perspective projections.
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.set_eye(self.eye);
+        camera.set_orientation(self.orientation);
+        if let Some(value) = self.fovy {
+            match camera.projection_mut() {
+                Projection::Perspective { fovy, .. } | Projection::ReverseInfinite { fovy, .. } => {
+                    *fovy = value
+                }
+                Projection::Orthographic { .. } => {}
+            }
+        }
+    }
+}
+
+/// Scripted camera motion. Positions follow a Catmull-Rom spline through
+/// the keyframes, with tangents scaled to their timing. Orientations are
+/// slerped and the fov is lerped.
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct CameraPath {
+    pub mode: PlayMode,
+    /// Sorted by time.
+    keyframes: Vec<Keyframe>,
+}
+
+impl CameraPath {
+    pub fn new(mode: PlayMode) -> Self {
+        Self {
+            mode,
+            keyframes: Vec::new(),
+        }
+    }
+
+    pub async fn load(path: &str) -> anyhow::Result<Self> {
+        let mut camera_path: Self = load_json(path).await?;
+        camera_path
+            .keyframes
+            .sort_by(|a, b| a.time.total_cmp(&b.time));
+        Ok(camera_path)
+    }
+
+    pub async fn save(&self, path: &str) -> anyhow::Result<()> {
+        save_json(path, self).await
+    }
+
+    pub fn keyframes(&self) -> &[Keyframe] {
+        &self.keyframes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    /// Keeps the keyframes sorted, a keyframe with the same time as an
+    /// existing one goes after it.
+    pub fn insert(&mut self, keyframe: Keyframe) {
+        let index = self.keyframes.partition_point(|k| k.time <= keyframe.time);
+        self.keyframes.insert(index, keyframe);
+    }
+
+    pub fn clear(&mut self) {
+        self.keyframes.clear();
+    }
+
+    /// From the first keyframe to the last.
+    pub fn duration(&self) -> f32 {
+        match (self.keyframes.first(), self.keyframes.last()) {
+            (Some(first), Some(last)) => last.time - first.time,
+            _ => 0.0,
+        }
+    }
+
+    /// Whether a [`PlayMode::Once`] path is over after playing for `time`
+    /// seconds. Other modes never finish.
+    pub fn is_finished(&self, time: f32) -> bool {
+        self.mode == PlayMode::Once && time >= self.duration()
+    }
+
+    /// The camera after playing for `time` seconds, `None` without
+    /// keyframes.
+    pub fn sample(&self, time: f32) -> Option<Keyframe> {
+        let first = self.keyframes.first()?;
+        let time = first.time + self.wrap(time);
+
+        let next = self.keyframes.partition_point(|k| k.time <= time);
+        if next == 0 {
+            return Some(*first);
+        }
+        if next == self.keyframes.len() {
+            return self.keyframes.last().copied();
+        }
+
+        let k1 = &self.keyframes[next - 1];
+        let k2 = &self.keyframes[next];
+        let span = k2.time - k1.time;
+        let t = if span > 0.0 {
+            (time - k1.time) / span
+        } else {
+            1.0
+        };
+
+        // The ends repeat the first and last keyframe, one span away.
+        let (t0, p0) = match next.checked_sub(2) {
+            Some(i) => (self.keyframes[i].time, self.keyframes[i].eye),
+            None => (k1.time - span, k1.eye),
+        };
+        let (t3, p3) = match self.keyframes.get(next + 1) {
+            Some(k3) => (k3.time, k3.eye),
+            None => (k2.time + span, k2.eye),
+        };
+        // In units per second, so the velocity is continuous across
+        // keyframes however unevenly they're spaced.
+        let m1 = velocity(t0, p0, k2.time, k2.eye);
+        let m2 = velocity(k1.time, k1.eye, t3, p3);
+
+        let fovy = match (k1.fovy, k2.fovy) {
+            (Some(a), Some(b)) => Some(a + (b - a) * t),
+            (fovy, _) => fovy,
+        };
+
+        Some(Keyframe {
+            time,
+            eye: hermite(k1.eye, m1 * span, k2.eye, m2 * span, t),
+            orientation: k1.orientation.slerp(k2.orientation, t),
+            fovy,
+        })
+    }
+
+    /// `time` folded into `0..=duration` according to the mode.
+    fn wrap(&self, time: f32) -> f32 {
+        let duration = self.duration();
+        if duration <= 0.0 {
+            return 0.0;
+        }
+        match self.mode {
+            PlayMode::Once => time.clamp(0.0, duration),
+            PlayMode::Loop => time.rem_euclid(duration),
+            PlayMode::PingPong => {
+                let time = time.rem_euclid(2.0 * duration);
+                if time > duration {
+                    2.0 * duration - time
+                } else {
+                    time
+                }
+            }
+        }
+    }
+}
+
+/// The Catmull-Rom tangent at a keyframe, from its neighbours.
+fn velocity(t0: f32, p0: glam::Vec3, t1: f32, p1: glam::Vec3) -> glam::Vec3 {
+    let dt = t1 - t0;
+    if dt > 0.0 {
+        (p1 - p0) / dt
+    } else {
+        glam::Vec3::ZERO
+    }
+}
+
+/// Passes through `p1` at `t == 0` and `p2` at `t == 1`, with tangents `m1`
+/// and `m2` per unit of `t`.
+fn hermite(p1: glam::Vec3, m1: glam::Vec3, p2: glam::Vec3, m2: glam::Vec3, t: f32) -> glam::Vec3 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (2.0 * t3 - 3.0 * t2 + 1.0) * p1
+        + (t3 - 2.0 * t2 + t) * m1
+        + (-2.0 * t3 + 3.0 * t2) * p2
+        + (t3 - t2) * m2
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    fn keyframe(time: f32, x: f32, yaw: f32) -> Keyframe {
+        Keyframe {
+            time,
+            eye: glam::vec3(x, 0.0, 0.0),
+            orientation: glam::Quat::from_rotation_y(yaw),
+            fovy: Some(1.0 + time),
+        }
+    }
+
+    fn path(mode: PlayMode) -> CameraPath {
+        let mut path = CameraPath::new(mode);
+        // Out of order on purpose.
+        path.insert(keyframe(3.0, 4.0, 0.0));
+        path.insert(keyframe(1.0, 0.0, 0.0));
+        path.insert(keyframe(2.0, 1.0, FRAC_PI_2));
+        path
+    }
+
+    #[test]
+    fn passes_through_keyframes() {
+        let path = path(PlayMode::Once);
+        assert_eq!(path.duration(), 2.0);
+        for (time, keyframe) in [0.0, 1.0, 2.0].into_iter().zip(path.keyframes()) {
+            let sample = path.sample(time).unwrap();
+            assert!(sample.eye.abs_diff_eq(keyframe.eye, EPSILON));
+            assert!(sample
+                .orientation
+                .abs_diff_eq(keyframe.orientation, EPSILON));
+            assert_eq!(sample.fovy, keyframe.fovy);
+        }
+    }
+
+    #[test]
+    fn interpolates_between_keyframes() {
+        let path = path(PlayMode::Once);
+        let sample = path.sample(0.5).unwrap();
+        // The spline eases out of the first keyframe, a lerp would give 0.5.
+        assert!((sample.eye.x - 0.3125).abs() < EPSILON);
+        assert!((sample.fovy.unwrap() - 2.5).abs() < EPSILON);
+
+        let halfway = path.sample(1.5).unwrap().orientation;
+        let expected = glam::Quat::from_rotation_y(FRAC_PI_2 * 0.5);
+        assert!(halfway.abs_diff_eq(expected, EPSILON));
+    }
+
+    #[test]
+    fn velocity_is_continuous_across_uneven_keyframes() {
+        let mut path = CameraPath::new(PlayMode::Once);
+        path.insert(keyframe(0.0, 0.0, 0.0));
+        path.insert(keyframe(1.0, 1.0, 0.0));
+        path.insert(keyframe(10.0, 3.0, 0.0));
+        path.insert(keyframe(11.0, 8.0, 0.0));
+
+        let eye_x = |time| path.sample(time).unwrap().eye.x;
+        let h = 1e-3;
+        for time in [1.0, 10.0] {
+            let before = (eye_x(time) - eye_x(time - h)) / h;
+            let after = (eye_x(time + h) - eye_x(time)) / h;
+            assert!((before - after).abs() < 0.05, "{before} != {after} at {time}");
+        }
+        // The tangent at 1 spans from 0 to 10.
+        assert!(((eye_x(1.0 + h) - eye_x(1.0 - h)) / (2.0 * h) - 0.3).abs() < 0.01);
+    }
+
+    #[test]
+    fn modes_wrap_time() {
+        let eye_x = |path: &CameraPath, time| path.sample(time).unwrap().eye.x;
+
+        let once = path(PlayMode::Once);
+        assert_eq!(eye_x(&once, 10.0), 4.0);
+        assert_eq!(eye_x(&once, -1.0), 0.0);
+        assert!(once.is_finished(2.0));
+
+        let looping = path(PlayMode::Loop);
+        assert!((eye_x(&looping, 2.5) - eye_x(&looping, 0.5)).abs() < EPSILON);
+        assert!(!looping.is_finished(100.0));
+
+        let ping_pong = path(PlayMode::PingPong);
+        assert!((eye_x(&ping_pong, 3.0) - 1.0).abs() < EPSILON);
+        assert!((eye_x(&ping_pong, 4.0) - 0.0).abs() < EPSILON);
+        assert!((eye_x(&ping_pong, 2.5) - eye_x(&ping_pong, 1.5)).abs() < EPSILON);
+    }
+
+    #[test]
+    fn empty_and_single_keyframe() {
+        let mut path = CameraPath::default();
+        assert_eq!(path.sample(1.0), None);
+        path.insert(keyframe(5.0, 2.0, 0.0));
+        assert_eq!(path.sample(1.0).unwrap().eye.x, 2.0);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let path = path(PlayMode::PingPong);
+        let json = serde_json::to_string(&path).unwrap();
+        let loaded: CameraPath = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, path);
+    }
+}
@@ -0,0 +1,103 @@
+/// How a [`super::Camera`] maps view space to clip space. The aspect ratio
+/// comes from the camera, so every variant follows
+/// [`super::Camera::resize`].
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum Projection {
+    Perspective {
+        fovy: f32,
+        near: f32,
+        far: f32,
+    },
+    /// Perspective with the far plane at infinity and depth running from 1
+    /// at `near` to 0 at infinity. Spreads float precision evenly over the
+    /// scene, but needs [`Projection::depth_clear`] and
+    /// [`Projection::depth_compare`] instead of the usual 1.0 and `Less`.
+    ReverseInfinite {
+        fovy: f32,
+        near: f32,
+    },
+    /// `size` is the visible height in world units at a `zoom` of 1,
+    /// doubling `zoom` halves it.
+    Orthographic {
+        size: f32,
+        zoom: f32,
+        near: f32,
+        far: f32,
+    },
+}
+
+impl Projection {
+    pub fn matrix(&self, aspect: f32) -> glam::Mat4 {
+        match *self {
+            Self::Perspective { fovy, near, far } => {
+                glam::Mat4::perspective_rh(fovy, aspect, near, far)
+            }
+            Self::ReverseInfinite { fovy, near } => {
+                glam::Mat4::perspective_infinite_reverse_rh(fovy, aspect, near)
+            }
+            Self::Orthographic {
+                size,
+                zoom,
+                near,
+                far,
+            } => {
+                let half_height = size * 0.5 / zoom;
+                let half_width = half_height * aspect;
+                glam::Mat4::orthographic_rh(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    near,
+                    far,
+                )
+            }
+        }
+    }
+
+    /// `None` for orthographic projections.
+    pub fn fovy(&self) -> Option<f32> {
+        match *self {
+            Self::Perspective { fovy, .. } | Self::ReverseInfinite { fovy, .. } => Some(fovy),
+            Self::Orthographic { .. } => None,
+        }
+    }
+
+    pub fn near(&self) -> f32 {
+        match *self {
+            Self::Perspective { near, .. }
+            | Self::ReverseInfinite { near, .. }
+            | Self::Orthographic { near, .. } => near,
+        }
+    }
+
+    /// Infinite for [`Projection::ReverseInfinite`].
+    pub fn far(&self) -> f32 {
+        match *self {
+            Self::Perspective { far, .. } | Self::Orthographic { far, .. } => far,
+            Self::ReverseInfinite { .. } => f32::INFINITY,
+        }
+    }
+
+    pub fn is_reverse_z(&self) -> bool {
+        matches!(self, Self::ReverseInfinite { .. })
+    }
+
+    /// What to clear a depth texture to before drawing with this projection.
+    pub fn depth_clear(&self) -> f32 {
+        if self.is_reverse_z() {
+            0.0
+        } else {
+            1.0
+        }
+    }
+
+    /// The depth test for pipelines drawing with this projection.
+    pub fn depth_compare(&self) -> wgpu::CompareFunction {
+        if self.is_reverse_z() {
+            wgpu::CompareFunction::Greater
+        } else {
+            wgpu::CompareFunction::Less
+        }
+    }
+}
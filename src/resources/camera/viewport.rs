@@ -0,0 +1,114 @@
+/// A part of the render target as fractions of its size, with the origin at
+/// the top left like window coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Viewport {
+    pub const FULL: Self = Self::new(0.0, 0.0, 1.0, 1.0);
+
+    pub const fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// The pixels covered in a target of the given size. Edges are rounded,
+    /// so viewports sharing an edge neither overlap nor leave a gap.
+    pub fn pixels(&self, width: u32, height: u32) -> PixelRect {
+        let edge = |fraction: f32, size: u32| {
+            (fraction.clamp(0.0, 1.0) * size as f32).round() as u32
+        };
+        let (left, right) = (edge(self.x, width), edge(self.x + self.width, width));
+        let (top, bottom) = (edge(self.y, height), edge(self.y + self.height, height));
+        PixelRect {
+            x: left,
+            y: top,
+            width: right.saturating_sub(left),
+            height: bottom.saturating_sub(top),
+        }
+    }
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+/// In physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl PixelRect {
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x as f64
+            && y >= self.y as f64
+            && x < (self.x + self.width) as f64
+            && y < (self.y + self.height) as f64
+    }
+
+    /// Restricts drawing in `pass` to the rectangle, both the viewport
+    /// transform and the scissor.
+    pub fn apply(&self, pass: &mut wgpu::RenderPass<'_>) {
+        pass.set_viewport(
+            self.x as f32,
+            self.y as f32,
+            self.width as f32,
+            self.height as f32,
+            0.0,
+            1.0,
+        );
+        pass.set_scissor_rect(self.x, self.y, self.width, self.height);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neighbours_tile_without_gaps() {
+        let (width, height) = (101, 77);
+        let left = Viewport::new(0.0, 0.0, 1.0 / 3.0, 1.0).pixels(width, height);
+        let right = Viewport::new(1.0 / 3.0, 0.0, 2.0 / 3.0, 1.0).pixels(width, height);
+        assert_eq!(left.x, 0);
+        assert_eq!(left.x + left.width, right.x);
+        assert_eq!(right.x + right.width, width);
+        assert_eq!(left.height, height);
+    }
+
+    #[test]
+    fn clamps_to_the_target() {
+        let rect = Viewport::new(0.75, -0.5, 0.5, 1.0).pixels(100, 100);
+        assert_eq!(
+            rect,
+            PixelRect {
+                x: 75,
+                y: 0,
+                width: 25,
+                height: 50
+            }
+        );
+        assert!(rect.contains(75.0, 49.5));
+        assert!(!rect.contains(74.9, 10.0));
+        assert!(!rect.contains(80.0, 50.0));
+        assert!(Viewport::new(0.5, 0.5, 0.0, 0.5).pixels(100, 100).is_empty());
+    }
+}
@@ -1,5 +1,32 @@
+use std::path::Path;
+
+use super::vfs;
+
+/// Serialization formats supported by [`load_serialized`] and
+/// [`save_serialized`], picked by file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// `.json`
+    Json,
+    /// `.ron`
+    Ron,
+    /// `.bin`, compact binary encoded with postcard
+    Postcard,
+}
+
+impl Format {
+    pub fn from_path(path: &str) -> anyhow::Result<Self> {
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Ok(Self::Json),
+            Some("ron") => Ok(Self::Ron),
+            Some("bin") => Ok(Self::Postcard),
+            _ => anyhow::bail!("Unknown serialization format for {path:?}"),
+        }
+    }
+}
+
 pub async fn save_text(path: &str, contents: &str) -> anyhow::Result<()> {
-    Ok(async_fs::write(path, contents).await?)
+    save_binary(path, contents.as_bytes()).await
 }
 
 pub async fn save_json(path: &str, contents: impl serde::Serialize) -> anyhow::Result<()> {
@@ -7,8 +34,32 @@ pub async fn save_json(path: &str, contents: impl serde::Serialize) -> anyhow::R
     save_text(path, &text).await
 }
 
+pub async fn save_ron(path: &str, contents: impl serde::Serialize) -> anyhow::Result<()> {
+    let text = ron::ser::to_string_pretty(&contents, Default::default())?;
+    save_text(path, &text).await
+}
+
+pub async fn save_postcard(path: &str, contents: impl serde::Serialize) -> anyhow::Result<()> {
+    let data = postcard::to_stdvec(&contents)?;
+    save_binary(path, &data).await
+}
+
+pub async fn save_serialized(path: &str, contents: impl serde::Serialize) -> anyhow::Result<()> {
+    match Format::from_path(path)? {
+        Format::Json => save_json(path, contents).await,
+        Format::Ron => save_ron(path, contents).await,
+        Format::Postcard => save_postcard(path, contents).await,
+    }
+}
+
+/// Writes to a temporary file first and renames it over `path`, so `path`
+/// always holds either the old or the new contents.
+pub async fn save_binary(path: &str, contents: &[u8]) -> anyhow::Result<()> {
+    vfs::get().write(path, contents).await
+}
+
 pub async fn load_text(path: &str) -> anyhow::Result<String> {
-    Ok(async_fs::read_to_string(path).await?)
+    Ok(String::from_utf8(load_binary(path).await?)?)
 }
 
 pub async fn load_json<T>(path: &str) -> anyhow::Result<T>
@@ -20,6 +71,108 @@ where
     Ok(data)
 }
 
+pub async fn load_ron<T>(path: &str) -> anyhow::Result<T>
+where
+    T: for<'a> serde::Deserialize<'a>,
+{
+    let text = load_text(path).await?;
+    let data = ron::from_str(&text)?;
+    Ok(data)
+}
+
+pub async fn load_postcard<T>(path: &str) -> anyhow::Result<T>
+where
+    T: for<'a> serde::Deserialize<'a>,
+{
+    let data = load_binary(path).await?;
+    Ok(postcard::from_bytes(&data)?)
+}
+
+pub async fn load_serialized<T>(path: &str) -> anyhow::Result<T>
+where
+    T: for<'a> serde::Deserialize<'a>,
+{
+    match Format::from_path(path)? {
+        Format::Json => load_json(path).await,
+        Format::Ron => load_ron(path).await,
+        Format::Postcard => load_postcard(path).await,
+    }
+}
+
 pub async fn load_binary(path: &str) -> anyhow::Result<Vec<u8>> {
-    Ok(async_fs::read(path).await?)
-}
\ No newline at end of file
+    vfs::get().read(path).await
+}
+
+#[cfg(test)]
+mod tests {
+    use pollster::FutureExt;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+    struct Data {
+        name: String,
+        values: Vec<f32>,
+        nested: Option<(u8, bool)>,
+    }
+
+    fn data() -> Data {
+        Data {
+            name: "data".to_string(),
+            values: vec![0.5, -1.0],
+            nested: Some((3, true)),
+        }
+    }
+
+    /// Absolute paths go straight to disk, the standard vfs is fine.
+    fn path(dir: &tempfile::TempDir, name: &str) -> String {
+        dir.path().join(name).to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn round_trips_every_format() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, format) in [
+            ("data.json", Format::Json),
+            ("data.ron", Format::Ron),
+            ("data.bin", Format::Postcard),
+        ] {
+            let path = path(&dir, name);
+            assert_eq!(Format::from_path(&path).unwrap(), format);
+            save_serialized(&path, data()).block_on().unwrap();
+            let loaded: Data = load_serialized(&path).block_on().unwrap();
+            assert_eq!(loaded, data());
+        }
+
+        let json: Data = load_json(&path(&dir, "data.json")).block_on().unwrap();
+        let ron: Data = load_ron(&path(&dir, "data.ron")).block_on().unwrap();
+        let postcard: Data = load_postcard(&path(&dir, "data.bin")).block_on().unwrap();
+        assert_eq!([json, ron, postcard], [data(), data(), data()]);
+    }
+
+    #[test]
+    fn rejects_unknown_formats() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = path(&dir, "data.toml");
+        assert!(Format::from_path(&path).is_err());
+        assert!(save_serialized(&path, data()).block_on().is_err());
+        assert!(!Path::new(&path).exists());
+    }
+
+    #[test]
+    fn saving_replaces_the_whole_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = path(&dir, "data.json");
+        std::fs::write(&path, "x".repeat(1000)).unwrap();
+
+        save_json(&path, data()).block_on().unwrap();
+        let loaded: Data = load_json(&path).block_on().unwrap();
+        assert_eq!(loaded, data());
+
+        let files: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(files, ["data.json"]);
+    }
+}
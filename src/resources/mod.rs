@@ -1,4 +1,11 @@
+pub mod bounds;
 pub mod camera;
 pub mod texture;
 pub mod fs;
-pub mod buffer;
\ No newline at end of file
+pub mod buffer;
+pub mod pack;
+pub mod picking;
+pub mod ray;
+pub mod vfs;
+#[cfg(feature = "hot-reload")]
+pub mod watcher;
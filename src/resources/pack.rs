@@ -0,0 +1,409 @@
+use std::{
+    collections::HashMap,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use futures_lite::{AsyncReadExt, AsyncSeekExt};
+
+const MAGIC: &[u8; 4] = b"WTPK";
+const VERSION: u32 = 1;
+const HEADER_SIZE: u64 = MAGIC.len() as u64 + 4 + 4;
+/// An index entry without its path.
+const ENTRY_SIZE: u64 = 2 + 8 * 3 + 1 + 32;
+/// LZ4 can't compress better than this, a larger size in the index means
+/// it's corrupt.
+const MAX_LZ4_RATIO: u64 = 255;
+
+/// File name the [`super::vfs`] looks for next to the executable.
+pub const DEFAULT_PACK: &str = "assets.pak";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Lz4,
+}
+
+impl Compression {
+    fn from_u8(value: u8) -> anyhow::Result<Self> {
+        match value {
+            0 => Ok(Self::None),
+            1 => Ok(Self::Lz4),
+            _ => anyhow::bail!("Unknown compression {value}"),
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Lz4 => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub offset: u64,
+    pub stored_size: u64,
+    pub size: u64,
+    pub compression: Compression,
+    pub hash: [u8; 32],
+}
+
+/// An indexed archive of asset files.
+///
+/// Layout, all integers little endian:
+///
+/// ```text
+/// magic "WTPK" | version: u32 | entry count: u32
+/// per entry: path len: u16 | path: utf8 | offset: u64 | stored size: u64
+///            | size: u64 | compression: u8 | blake3 hash: [u8; 32]
+/// file data
+/// ```
+///
+/// Offsets are from the start of the archive and hashes are of the
+/// uncompressed contents.
+#[derive(Debug)]
+pub struct Pack {
+    path: PathBuf,
+    entries: HashMap<String, Entry>,
+}
+
+impl Pack {
+    /// Reads the index of the archive at `path`. File data is only read on
+    /// demand.
+    ///
+    /// Every size in the index is checked against the file length, so a
+    /// corrupt archive is an error rather than a huge allocation.
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = std::fs::File::open(&path)?;
+        let file_len = file.metadata()?.len();
+        let mut file = io::BufReader::new(file);
+
+        let mut magic = [0; 4];
+        file.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            anyhow::bail!("{path:?} is not a pack file");
+        }
+
+        let version = read_u32(&mut file)?;
+        if version != VERSION {
+            anyhow::bail!("{path:?} has unsupported version {version}");
+        }
+
+        let count = read_u32(&mut file)?;
+        if u64::from(count) * ENTRY_SIZE > file_len.saturating_sub(HEADER_SIZE) {
+            anyhow::bail!("{path:?} is truncated, its index has {count} entries");
+        }
+        let mut entries = HashMap::with_capacity(count as _);
+        let mut index_end = HEADER_SIZE;
+        for _ in 0..count {
+            let len = read_u16(&mut file)?;
+            index_end += ENTRY_SIZE + u64::from(len);
+            if index_end > file_len {
+                anyhow::bail!("{path:?} is truncated");
+            }
+            let mut name = vec![0; len as usize];
+            file.read_exact(&mut name)?;
+            let name = String::from_utf8(name)?;
+
+            let offset = read_u64(&mut file)?;
+            let stored_size = read_u64(&mut file)?;
+            let size = read_u64(&mut file)?;
+            let compression = Compression::from_u8(read_u8(&mut file)?)?;
+            let mut hash = [0; 32];
+            file.read_exact(&mut hash)?;
+
+            let in_bounds = offset
+                .checked_add(stored_size)
+                .is_some_and(|end| end <= file_len);
+            let size_valid = match compression {
+                Compression::None => size == stored_size,
+                Compression::Lz4 => size <= stored_size.saturating_mul(MAX_LZ4_RATIO),
+            };
+            if !in_bounds || !size_valid {
+                anyhow::bail!("{name:?} in {path:?} has a corrupt index entry");
+            }
+
+            entries.insert(
+                name,
+                Entry {
+                    offset,
+                    stored_size,
+                    size,
+                    compression,
+                    hash,
+                },
+            );
+        }
+
+        Ok(Self { path, entries })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    pub fn entry(&self, name: &str) -> Option<&Entry> {
+        self.entries.get(name)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &Entry)> {
+        self.entries
+            .iter()
+            .map(|(name, entry)| (name.as_str(), entry))
+    }
+
+    /// Reads, decompresses and verifies a file. Returns `None` if the
+    /// archive doesn't contain `name`.
+    pub async fn read(&self, name: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let Some(entry) = self.entries.get(name) else {
+            return Ok(None);
+        };
+
+        let mut file = async_fs::File::open(&self.path).await?;
+        file.seek(io::SeekFrom::Start(entry.offset)).await?;
+        let mut stored = vec![0; usize::try_from(entry.stored_size)?];
+        file.read_exact(&mut stored).await?;
+
+        let data = match entry.compression {
+            Compression::None => stored,
+            Compression::Lz4 => lz4_flex::decompress(&stored, usize::try_from(entry.size)?)?,
+        };
+
+        if *blake3::hash(&data).as_bytes() != entry.hash {
+            anyhow::bail!("{name:?} in {:?} is corrupted", self.path);
+        }
+
+        Ok(Some(data))
+    }
+}
+
+/// Builds a [`Pack`] in memory and writes it out in one go.
+#[derive(Debug, Default)]
+pub struct PackWriter {
+    files: Vec<(String, Entry, Vec<u8>)>,
+}
+
+impl PackWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file. Compression is skipped if it wouldn't make the file
+    /// smaller.
+    pub fn add(&mut self, name: &str, data: &[u8], compression: Compression) -> &mut Self {
+        let hash = *blake3::hash(data).as_bytes();
+        let (compression, stored) = match compression {
+            Compression::Lz4 => {
+                let compressed = lz4_flex::compress(data);
+                if compressed.len() < data.len() {
+                    (Compression::Lz4, compressed)
+                } else {
+                    (Compression::None, data.to_vec())
+                }
+            }
+            Compression::None => (Compression::None, data.to_vec()),
+        };
+        let entry = Entry {
+            offset: 0,
+            stored_size: stored.len() as u64,
+            size: data.len() as u64,
+            compression,
+            hash,
+        };
+        self.files.push((name.to_owned(), entry, stored));
+        self
+    }
+
+    pub fn write(&self, mut out: impl Write) -> anyhow::Result<()> {
+        let index_size: u64 = self
+            .files
+            .iter()
+            .map(|(name, ..)| ENTRY_SIZE + name.len() as u64)
+            .sum();
+        let mut offset = HEADER_SIZE + index_size;
+
+        out.write_all(MAGIC)?;
+        out.write_all(&VERSION.to_le_bytes())?;
+        out.write_all(&(self.files.len() as u32).to_le_bytes())?;
+
+        for (name, entry, _) in &self.files {
+            let len: u16 = name
+                .len()
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("Path too long: {name:?}"))?;
+            out.write_all(&len.to_le_bytes())?;
+            out.write_all(name.as_bytes())?;
+            out.write_all(&offset.to_le_bytes())?;
+            out.write_all(&entry.stored_size.to_le_bytes())?;
+            out.write_all(&entry.size.to_le_bytes())?;
+            out.write_all(&[entry.compression.to_u8()])?;
+            out.write_all(&entry.hash)?;
+            offset += entry.stored_size;
+        }
+
+        for (_, _, stored) in &self.files {
+            out.write_all(stored)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn read_u8(r: &mut impl Read) -> io::Result<u8> {
+    let mut bytes = [0; 1];
+    r.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+fn read_u16(r: &mut impl Read) -> io::Result<u16> {
+    let mut bytes = [0; 2];
+    r.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_u32(r: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    r.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(r: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    r.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use pollster::FutureExt;
+
+    use super::*;
+
+    fn write_pack(dir: &Path, writer: &PackWriter) -> PathBuf {
+        let path = dir.join(DEFAULT_PACK);
+        let mut data = Vec::new();
+        writer.write(&mut data).unwrap();
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    /// Not compressible, xorshift output.
+    fn noise(len: usize) -> Vec<u8> {
+        let mut state = 0x2545_f491_u32;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let text = "fn main() {}\n".repeat(100);
+        let mut writer = PackWriter::new();
+        writer
+            .add("shaders/a.wgsl", text.as_bytes(), Compression::Lz4)
+            .add("empty", &[], Compression::None)
+            .add("noise.bin", &noise(1000), Compression::None);
+        let pack = Pack::open(write_pack(dir.path(), &writer)).unwrap();
+
+        assert_eq!(pack.entries().count(), 3);
+        assert!(pack.contains("shaders/a.wgsl"));
+        assert_eq!(
+            pack.read("shaders/a.wgsl").block_on().unwrap().unwrap(),
+            text.as_bytes()
+        );
+        assert_eq!(pack.read("empty").block_on().unwrap().unwrap(), b"");
+        assert_eq!(
+            pack.read("noise.bin").block_on().unwrap().unwrap(),
+            noise(1000)
+        );
+        assert_eq!(pack.read("missing").block_on().unwrap(), None);
+    }
+
+    #[test]
+    fn stores_what_lz4_does_not_shrink() {
+        let dir = tempfile::tempdir().unwrap();
+        let noise = noise(4096);
+        let mut writer = PackWriter::new();
+        writer.add("repetitive", &[7; 4096], Compression::Lz4);
+        writer.add("noise", &noise, Compression::Lz4);
+        let pack = Pack::open(write_pack(dir.path(), &writer)).unwrap();
+
+        let repetitive = pack.entry("repetitive").unwrap();
+        assert_eq!(repetitive.compression, Compression::Lz4);
+        assert!(repetitive.stored_size < repetitive.size);
+
+        let noise_entry = pack.entry("noise").unwrap();
+        assert_eq!(noise_entry.compression, Compression::None);
+        assert_eq!(noise_entry.stored_size, noise_entry.size);
+        assert_eq!(pack.read("noise").block_on().unwrap().unwrap(), noise);
+    }
+
+    #[test]
+    fn rejects_corrupt_headers() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = PackWriter::new();
+        writer.add("file", &[1; 100], Compression::Lz4);
+        let mut data = Vec::new();
+        writer.write(&mut data).unwrap();
+
+        let open = |data: &[u8]| {
+            let path = dir.path().join("corrupt.pak");
+            std::fs::write(&path, data).unwrap();
+            Pack::open(path)
+        };
+        // Entry fields, after the header and the 2 + 4 bytes of "file".
+        let offset = HEADER_SIZE as usize + 6;
+        let stored_size = offset + 8;
+        let size = stored_size + 8;
+
+        assert!(open(&data).is_ok());
+        assert!(open(b"nope").is_err());
+
+        let mut huge_count = data.clone();
+        huge_count[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(open(&huge_count).is_err());
+
+        assert!(open(&data[..data.len() - 1]).is_err());
+        assert!(open(&data[..HEADER_SIZE as usize + 10]).is_err());
+
+        let mut past_end = data.clone();
+        past_end[offset..offset + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(open(&past_end).is_err());
+
+        let mut huge_stored = data.clone();
+        huge_stored[stored_size..stored_size + 8].copy_from_slice(&(1u64 << 40).to_le_bytes());
+        assert!(open(&huge_stored).is_err());
+
+        let mut huge_size = data.clone();
+        huge_size[size..size + 8].copy_from_slice(&(1u64 << 40).to_le_bytes());
+        assert!(open(&huge_size).is_err());
+    }
+
+    #[test]
+    fn detects_corrupt_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = PackWriter::new();
+        writer.add("file", b"contents", Compression::None);
+        let path = write_pack(dir.path(), &writer);
+
+        let mut data = std::fs::read(&path).unwrap();
+        *data.last_mut().unwrap() ^= 1;
+        std::fs::write(&path, data).unwrap();
+
+        let pack = Pack::open(&path).unwrap();
+        assert!(pack.read("file").block_on().is_err());
+    }
+}
@@ -0,0 +1,267 @@
+use crate::context::Context;
+
+use super::{camera::Camera, texture::Texture};
+
+/// The ID the picking target is cleared to, so no object can use it.
+pub const NO_OBJECT: u32 = 0;
+
+pub const ID_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Uint;
+pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+/// The depth again, as a color. Some backends, like GL, can only copy whole
+/// depth textures, so the pixel under the cursor is copied out of this one.
+pub const DEPTH_VALUE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Float;
+
+/// What was under the cursor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pick {
+    /// In physical pixels.
+    pub x: u32,
+    pub y: u32,
+    /// [`NO_OBJECT`] if nothing was drawn there.
+    pub id: u32,
+    /// Reconstructed from depth, `None` if nothing was drawn there.
+    pub position: Option<glam::Vec3>,
+}
+
+struct PendingPick {
+    x: u32,
+    y: u32,
+    size: glam::Vec2,
+    inv_view_proj: glam::Mat4,
+    receiver: flume::Receiver<Result<(), wgpu::BufferAsyncError>>,
+}
+
+/// Object IDs and depth rendered off screen, with the pixel under the
+/// cursor read back without stalling. Pipelines drawing into it need
+/// [`ID_FORMAT`] and [`DEPTH_VALUE_FORMAT`] color targets, in that order,
+/// and a [`DEPTH_FORMAT`] depth target. The fragment depth goes into the
+/// second color target.
+///
+/// Only one pick is in flight at a time. [`PickingTarget::request`] holds on
+/// to the latest position until the previous one has been read back.
+///
+/// The targets are only created for the first pass, nothing that never
+/// picks pays for them.
+pub struct PickingTarget {
+    size: (u32, u32),
+    targets: Option<Targets>,
+    /// The ID, then the depth.
+    readback: wgpu::Buffer,
+    requested: Option<(u32, u32)>,
+    pending: Option<PendingPick>,
+}
+
+struct Targets {
+    ids: Texture,
+    depth_values: Texture,
+    depth: Texture,
+}
+
+impl Targets {
+    fn new(context: &Context, (width, height): (u32, u32)) -> Self {
+        let usage = wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC;
+        Self {
+            ids: Texture::new(&context.device, width, height, ID_FORMAT, usage),
+            depth_values: Texture::new(&context.device, width, height, DEPTH_VALUE_FORMAT, usage),
+            depth: Texture::new(
+                &context.device,
+                width,
+                height,
+                DEPTH_FORMAT,
+                wgpu::TextureUsages::RENDER_ATTACHMENT,
+            ),
+        }
+    }
+}
+
+impl PickingTarget {
+    pub fn new(context: &Context, width: u32, height: u32) -> Self {
+        let readback = context.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("PickingTarget::readback"),
+            size: 8,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        Self {
+            size: (width, height),
+            targets: None,
+            readback,
+            requested: None,
+            pending: None,
+        }
+    }
+
+    pub fn resize(&mut self, context: &Context, width: u32, height: u32) {
+        self.size = (width, height);
+        if let Some(targets) = &mut self.targets {
+            targets.ids.resize(context, width, height);
+            targets.depth_values.resize(context, width, height);
+            targets.depth.resize(context, width, height);
+        }
+    }
+
+    /// Picks at a cursor position in physical pixels once the previous pick
+    /// is done.
+    pub fn request(&mut self, x: f64, y: f64) {
+        let (width, height) = self.size;
+        if x < 0.0 || y < 0.0 || x >= width as f64 || y >= height as f64 {
+            return;
+        }
+        self.requested = Some((x as u32, y as u32));
+    }
+
+    /// Whether to draw into the target this frame, with
+    /// [`PickingTarget::begin_pass`] and [`PickingTarget::finish`].
+    pub fn needs_pass(&self) -> bool {
+        self.requested.is_some() && self.pending.is_none()
+    }
+
+    /// Clears the targets for drawing the objects into, creating them first
+    /// if this is the first pass.
+    pub fn begin_pass<'e>(
+        &'e mut self,
+        context: &Context,
+        encoder: &'e mut wgpu::CommandEncoder,
+        camera: &Camera,
+    ) -> wgpu::RenderPass<'e> {
+        let size = self.size;
+        let targets = self
+            .targets
+            .get_or_insert_with(|| Targets::new(context, size));
+        // Both color targets are cleared to the same value, as the GL
+        // backend clears the ID target with the depth target's color. The
+        // depth value doesn't matter where the ID is NO_OBJECT.
+        let clear = wgpu::Operations {
+            load: wgpu::LoadOp::Clear(wgpu::Color {
+                r: NO_OBJECT as f64,
+                g: 0.0,
+                b: 0.0,
+                a: 0.0,
+            }),
+            store: wgpu::StoreOp::Store,
+        };
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("PickingTarget"),
+            color_attachments: &[
+                Some(wgpu::RenderPassColorAttachment {
+                    view: targets.ids.view(),
+                    resolve_target: None,
+                    ops: clear,
+                }),
+                Some(wgpu::RenderPassColorAttachment {
+                    view: targets.depth_values.view(),
+                    resolve_target: None,
+                    ops: clear,
+                }),
+            ],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: targets.depth.view(),
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(camera.projection().depth_clear()),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            ..Default::default()
+        })
+    }
+
+    /// Copies the requested pixel out of the targets and starts reading it
+    /// back. Submits `encoder` itself, as the read back can only start once
+    /// it is. `camera` must be the one the pass was drawn with.
+    pub fn finish(
+        &mut self,
+        context: &Context,
+        mut encoder: wgpu::CommandEncoder,
+        camera: &Camera,
+    ) {
+        if self.pending.is_some() {
+            return;
+        }
+        let Some(targets) = &self.targets else {
+            return;
+        };
+        let Some((x, y)) = self.requested.take() else {
+            return;
+        };
+
+        for (texture, offset) in [(&targets.ids, 0), (&targets.depth_values, 4)] {
+            encoder.copy_texture_to_buffer(
+                wgpu::ImageCopyTexture {
+                    texture: texture.texture(),
+                    mip_level: 0,
+                    origin: wgpu::Origin3d { x, y, z: 0 },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::ImageCopyBuffer {
+                    buffer: &self.readback,
+                    layout: wgpu::ImageDataLayout {
+                        offset,
+                        bytes_per_row: None,
+                        rows_per_image: None,
+                    },
+                },
+                wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+        context.queue.submit([encoder.finish()]);
+
+        let (sender, receiver) = flume::bounded(1);
+        self.readback
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = sender.send(result);
+            });
+        let size = targets.ids.texture().size();
+        self.pending = Some(PendingPick {
+            x,
+            y,
+            size: glam::vec2(size.width as f32, size.height as f32),
+            inv_view_proj: (camera.calc_proj() * camera.calc_view()).inverse(),
+            receiver,
+        });
+    }
+
+    /// The finished pick, if the read back has completed. Doesn't block.
+    pub fn poll(&mut self, context: &Context) -> Option<Pick> {
+        let pending = self.pending.as_ref()?;
+        context.device.poll(wgpu::Maintain::Poll);
+        let result = match pending.receiver.try_recv() {
+            Ok(result) => result,
+            Err(flume::TryRecvError::Empty) => return None,
+            Err(flume::TryRecvError::Disconnected) => Err(wgpu::BufferAsyncError),
+        };
+        let pending = self.pending.take()?;
+        if let Err(e) = result {
+            log::warn!("Picking read back failed: {e}");
+            return None;
+        }
+
+        let mapped = self.readback.slice(..).get_mapped_range();
+        let id = u32::from_ne_bytes(mapped[0..4].try_into().unwrap());
+        let depth = f32::from_ne_bytes(mapped[4..8].try_into().unwrap());
+        drop(mapped);
+        self.readback.unmap();
+
+        let position = (id != NO_OBJECT).then(|| {
+            // The center of the pixel.
+            let ndc = glam::vec3(
+                2.0 * (pending.x as f32 + 0.5) / pending.size.x - 1.0,
+                1.0 - 2.0 * (pending.y as f32 + 0.5) / pending.size.y,
+                depth,
+            );
+            pending.inv_view_proj.project_point3(ndc)
+        });
+
+        Some(Pick {
+            x: pending.x,
+            y: pending.y,
+            id,
+            position,
+        })
+    }
+}
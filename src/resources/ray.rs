@@ -0,0 +1,171 @@
+use super::bounds::{Aabb, Plane, Sphere};
+
+/// A half line. The intersection methods return how far along `direction`
+/// the first hit in front of `origin` is, so with a normalized direction
+/// that's the distance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: glam::Vec3,
+    pub direction: glam::Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: glam::Vec3, direction: glam::Vec3) -> Self {
+        Self { origin, direction }
+    }
+
+    pub fn at(&self, t: f32) -> glam::Vec3 {
+        self.origin + self.direction * t
+    }
+
+    /// Hits from either side. A ray lying in the plane misses.
+    pub fn intersect_plane(&self, plane: &Plane) -> Option<f32> {
+        let denom = plane.normal.dot(self.direction);
+        if denom.abs() <= f32::EPSILON {
+            return None;
+        }
+        let t = -plane.signed_distance(self.origin) / denom;
+        (t >= 0.0).then_some(t)
+    }
+
+    /// 0 if the origin is inside the box.
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        // Slab test, dividing by 0 gives infinities that sort themselves
+        // out unless the origin is on a slab boundary.
+        let inv = self.direction.recip();
+        let t0 = (aabb.min - self.origin) * inv;
+        let t1 = (aabb.max - self.origin) * inv;
+        let near = t0.min(t1).max_element().max(0.0);
+        let far = t0.max(t1).min_element();
+        (near <= far).then_some(near)
+    }
+
+    /// 0 if the origin is inside the sphere.
+    pub fn intersect_sphere(&self, sphere: &Sphere) -> Option<f32> {
+        let offset = self.origin - sphere.center;
+        let a = self.direction.length_squared();
+        let half_b = offset.dot(self.direction);
+        let c = offset.length_squared() - sphere.radius * sphere.radius;
+        if c <= 0.0 {
+            return Some(0.0);
+        }
+        let discriminant = half_b * half_b - a * c;
+        if discriminant < 0.0 || a <= f32::EPSILON {
+            return None;
+        }
+        let t = (-half_b - discriminant.sqrt()) / a;
+        (t >= 0.0).then_some(t)
+    }
+
+    /// Möller-Trumbore, hits both faces.
+    pub fn intersect_triangle(&self, a: glam::Vec3, b: glam::Vec3, c: glam::Vec3) -> Option<f32> {
+        let ab = b - a;
+        let ac = c - a;
+        let p = self.direction.cross(ac);
+        let det = ab.dot(p);
+        if det.abs() <= f32::EPSILON {
+            return None;
+        }
+        let inv_det = det.recip();
+        let to_origin = self.origin - a;
+        let u = to_origin.dot(p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = to_origin.cross(ab);
+        let v = self.direction.dot(q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = ac.dot(q) * inv_det;
+        (t >= 0.0).then_some(t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-5;
+
+    fn ray() -> Ray {
+        Ray::new(glam::vec3(0.0, 1.0, 5.0), glam::Vec3::NEG_Z)
+    }
+
+    fn assert_hit(hit: Option<f32>, expected: f32) {
+        let t = hit.expect("missed");
+        assert!((t - expected).abs() < EPSILON, "{t} != {expected}");
+    }
+
+    #[test]
+    fn planes() {
+        let ground = Plane::from_point_normal(glam::Vec3::ZERO, glam::Vec3::Y);
+        let down = Ray::new(
+            glam::vec3(1.0, 2.0, 3.0),
+            glam::vec3(0.0, -1.0, -1.0).normalize(),
+        );
+        assert_hit(down.intersect_plane(&ground), 2.0 * 2f32.sqrt());
+        assert!(down.at(down.intersect_plane(&ground).unwrap()).y.abs() < EPSILON);
+
+        // Parallel and pointing away.
+        assert_eq!(ray().intersect_plane(&ground), None);
+        let up = Ray::new(glam::Vec3::Y, glam::Vec3::Y);
+        assert_eq!(up.intersect_plane(&ground), None);
+    }
+
+    #[test]
+    fn aabbs() {
+        let aabb = Aabb::new(glam::vec3(-1.0, 0.0, 0.0), glam::Vec3::splat(2.0));
+        assert_hit(ray().intersect_aabb(&aabb), 3.0);
+        assert_hit(
+            Ray::new(glam::Vec3::ONE, glam::Vec3::X).intersect_aabb(&aabb),
+            0.0,
+        );
+        assert_eq!(
+            Ray::new(glam::vec3(0.0, 3.0, 5.0), glam::Vec3::NEG_Z).intersect_aabb(&aabb),
+            None
+        );
+        assert_eq!(
+            Ray::new(glam::vec3(0.0, 1.0, 5.0), glam::Vec3::Z).intersect_aabb(&aabb),
+            None
+        );
+    }
+
+    #[test]
+    fn spheres() {
+        let sphere = Sphere::new(glam::vec3(0.0, 1.0, 0.0), 1.0);
+        assert_hit(ray().intersect_sphere(&sphere), 4.0);
+        assert_hit(
+            Ray::new(glam::vec3(0.0, 1.0, 0.5), glam::Vec3::Z).intersect_sphere(&sphere),
+            0.0,
+        );
+        assert_eq!(
+            Ray::new(glam::vec3(0.0, 2.5, 5.0), glam::Vec3::NEG_Z).intersect_sphere(&sphere),
+            None
+        );
+        assert_eq!(
+            Ray::new(glam::vec3(0.0, 1.0, 5.0), glam::Vec3::Z).intersect_sphere(&sphere),
+            None
+        );
+    }
+
+    #[test]
+    fn triangles() {
+        let (a, b, c) = (
+            glam::vec3(-1.0, 0.0, 0.0),
+            glam::vec3(1.0, 0.0, 0.0),
+            glam::vec3(0.0, 2.0, 0.0),
+        );
+        assert_hit(ray().intersect_triangle(a, b, c), 5.0);
+        // Back face.
+        assert_hit(ray().intersect_triangle(a, c, b), 5.0);
+        assert_eq!(
+            Ray::new(glam::vec3(0.9, 1.5, 5.0), glam::Vec3::NEG_Z).intersect_triangle(a, b, c),
+            None
+        );
+        assert_eq!(
+            Ray::new(glam::vec3(0.0, 1.0, -5.0), glam::Vec3::NEG_Z).intersect_triangle(a, b, c),
+            None
+        );
+    }
+}
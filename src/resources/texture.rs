@@ -1,7 +1,6 @@
 use crate::context::Context;
 
 pub struct Texture {
-    #[allow(dead_code)]
     texture: wgpu::Texture,
     view: wgpu::TextureView,
     format: wgpu::TextureFormat,
@@ -9,11 +8,15 @@ pub struct Texture {
 }
 
 impl Texture {
+    pub fn new(device: &wgpu::Device, width: u32, height: u32, format: wgpu::TextureFormat, usage: wgpu::TextureUsages) -> Self {
+        let (texture, view) = create_2d(device, width, height, format, usage);
+        Self { texture, view, format, usage }
+    }
+
     pub fn depth_texture(device: &wgpu::Device, width: u32, height: u32) -> Self {
         let format = wgpu::TextureFormat::Depth32Float;
         let usage = wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING;
-        let (texture, view) = create_2d(device, width, height, format, usage);
-        Self { texture, view, format, usage }
+        Self::new(device, width, height, format, usage)
     }
 
     pub fn resize(&mut self, context: &Context, width: u32, height: u32) {
@@ -29,11 +32,15 @@ impl Texture {
     pub fn view(&self) -> &wgpu::TextureView {
         &self.view
     }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
 }
 
 fn create_2d(device: &wgpu::Device, width: u32, height: u32, format: wgpu::TextureFormat, usage: wgpu::TextureUsages) -> (wgpu::Texture, wgpu::TextureView) {
     let texture = device.create_texture(&wgpu::TextureDescriptor {
-        label: Some("depth_texture"),
+        label: Some("Texture"),
         size: wgpu::Extent3d {
             width,
             height,
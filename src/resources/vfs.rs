@@ -0,0 +1,351 @@
+use std::{
+    path::{Component, Path, PathBuf},
+    sync::{Arc, OnceLock},
+};
+
+use futures_lite::AsyncWriteExt;
+
+use super::pack::{self, Pack};
+
+/// Paths starting with this prefix refer to writable user data such as
+/// `config.json`. Every other relative path is a read-only asset.
+pub const USER_PREFIX: &str = "user:";
+
+const APP_NAME: &str = "wgpu-template";
+const OVERLAYS_VAR: &str = "WGPU_TEMPLATE_OVERLAYS";
+
+static VFS: OnceLock<Vfs> = OnceLock::new();
+
+/// Makes `vfs` the file system used by [`crate::resources::fs`]. This has to
+/// happen before anything is loaded, otherwise [`Vfs::standard`] is used.
+pub fn install(vfs: Vfs) -> anyhow::Result<()> {
+    VFS.set(vfs)
+        .map_err(|_| anyhow::anyhow!("Vfs already in use"))
+}
+
+pub fn get() -> &'static Vfs {
+    VFS.get_or_init(|| {
+        let mut vfs = Vfs::standard();
+        // Tests never touch the real user data.
+        if cfg!(test) {
+            vfs.user_dir = std::env::temp_dir().join(format!("{APP_NAME}-test-{}", std::process::id()));
+        }
+        vfs
+    })
+}
+
+#[derive(Debug, Clone)]
+pub enum Mount {
+    Dir(PathBuf),
+    Pack(Arc<Pack>),
+}
+
+/// Resolves asset paths against a list of mounts and user data paths
+/// against a single writable directory.
+///
+/// Assets are looked up in the mounts front to back and the first mount
+/// containing the file wins. Overlays are inserted at the front so they
+/// shadow everything mounted before them.
+///
+/// Asset and user data paths may only contain plain names, so `..` can't
+/// reach outside of a mount or the user dir.
+#[derive(Debug, Clone)]
+pub struct Vfs {
+    mounts: Vec<Mount>,
+    user_dir: PathBuf,
+}
+
+impl Vfs {
+    pub fn new(user_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            mounts: Vec::new(),
+            user_dir: user_dir.into(),
+        }
+    }
+
+    /// Looks up assets in the directories listed in `WGPU_TEMPLATE_OVERLAYS`,
+    /// then the development asset directory (debug builds only), then
+    /// `assets.pak` next to the executable if it exists and finally loose
+    /// files next to the executable. User data goes into the platform config
+    /// directory, or next to the executable if there isn't one.
+    pub fn standard() -> Self {
+        let exe_dir = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf))
+            .unwrap_or_else(|| PathBuf::from("."));
+        let user_dir = dirs::config_dir()
+            .map(|dir| dir.join(APP_NAME))
+            .unwrap_or_else(|| exe_dir.clone());
+
+        let mut vfs = Self::new(user_dir);
+        if cfg!(debug_assertions) {
+            vfs.mount(Mount::Dir(PathBuf::from(env!("CARGO_MANIFEST_DIR"))));
+        }
+        let pack_path = exe_dir.join(pack::DEFAULT_PACK);
+        if pack_path.is_file() {
+            match Pack::open(&pack_path) {
+                Ok(pack) => {
+                    vfs.mount(Mount::Pack(Arc::new(pack)));
+                }
+                Err(e) => log::error!("Unable to open {pack_path:?}: {e}"),
+            }
+        }
+        vfs.mount(Mount::Dir(exe_dir));
+        if let Some(overlays) = std::env::var_os(OVERLAYS_VAR) {
+            for dir in std::env::split_paths(&overlays)
+                .collect::<Vec<_>>()
+                .into_iter()
+                .rev()
+            {
+                vfs.mount_overlay(Mount::Dir(dir));
+            }
+        }
+        vfs
+    }
+
+    /// Adds a mount with the lowest priority.
+    pub fn mount(&mut self, mount: Mount) -> &mut Self {
+        self.mounts.push(mount);
+        self
+    }
+
+    /// Adds a mount with the highest priority.
+    pub fn mount_overlay(&mut self, mount: Mount) -> &mut Self {
+        self.mounts.insert(0, mount);
+        self
+    }
+
+    pub fn mounts(&self) -> &[Mount] {
+        &self.mounts
+    }
+
+    pub fn user_dir(&self) -> &Path {
+        &self.user_dir
+    }
+
+    /// The loose file on disk that `path` currently refers to, if any.
+    /// Returns `None` for files inside a [`Pack`]. Absolute paths are passed
+    /// through untouched.
+    pub fn real_path(&self, path: &str) -> Option<PathBuf> {
+        if let Some(path) = self.direct_path(path).ok()? {
+            return Some(path);
+        }
+
+        for mount in &self.mounts {
+            match mount {
+                Mount::Dir(dir) => {
+                    let real_path = dir.join(path);
+                    if real_path.is_file() {
+                        return Some(real_path);
+                    }
+                }
+                Mount::Pack(pack) => {
+                    if pack.contains(path) {
+                        return None;
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    pub async fn read(&self, path: &str) -> anyhow::Result<Vec<u8>> {
+        if let Some(real_path) = self.direct_path(path)? {
+            return Ok(async_fs::read(real_path).await?);
+        }
+
+        for mount in &self.mounts {
+            match mount {
+                Mount::Dir(dir) => {
+                    let real_path = dir.join(path);
+                    if real_path.is_file() {
+                        return Ok(async_fs::read(real_path).await?);
+                    }
+                }
+                Mount::Pack(pack) => {
+                    if let Some(data) = pack.read(path).await? {
+                        return Ok(data);
+                    }
+                }
+            }
+        }
+
+        anyhow::bail!("{path:?} not found in any mount")
+    }
+
+    /// Writes `contents` to a temporary file next to `path` and renames it
+    /// into place once it is flushed to disk, so a crash never leaves a
+    /// partially written file behind. If writing fails the temporary file
+    /// is removed again.
+    pub async fn write(&self, path: &str, contents: &[u8]) -> anyhow::Result<()> {
+        let real_path = self.writable_path(path)?;
+        if let Some(parent) = real_path.parent() {
+            async_fs::create_dir_all(parent).await?;
+        }
+
+        let mut temp_path = real_path.clone().into_os_string();
+        temp_path.push(".tmp");
+
+        let result = async {
+            let mut file = async_fs::File::create(&temp_path).await?;
+            file.write_all(contents).await?;
+            file.sync_all().await?;
+            drop(file);
+            async_fs::rename(&temp_path, real_path).await
+        }
+        .await;
+        if result.is_err() {
+            // The write error is the interesting one, this may fail too if
+            // the file was never created.
+            let _ = async_fs::remove_file(&temp_path).await;
+        }
+
+        Ok(result?)
+    }
+
+    fn writable_path(&self, path: &str) -> anyhow::Result<PathBuf> {
+        self.direct_path(path)?.ok_or_else(|| {
+            anyhow::anyhow!("{path:?} is a read-only asset, use {USER_PREFIX:?} for user data")
+        })
+    }
+
+    /// User data and absolute paths, which don't go through the mounts.
+    /// Fails for relative paths that could escape their directory.
+    fn direct_path(&self, path: &str) -> anyhow::Result<Option<PathBuf>> {
+        if let Some(path) = path.strip_prefix(USER_PREFIX) {
+            check_relative(path)?;
+            Ok(Some(self.user_dir.join(path)))
+        } else if Path::new(path).is_absolute() {
+            Ok(Some(PathBuf::from(path)))
+        } else {
+            check_relative(path)?;
+            Ok(None)
+        }
+    }
+}
+
+fn check_relative(path: &str) -> anyhow::Result<()> {
+    let plain = Path::new(path)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    anyhow::ensure!(plain, "{path:?} may only contain plain names");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pollster::FutureExt;
+
+    use super::*;
+
+    fn read(vfs: &Vfs, path: &str) -> anyhow::Result<String> {
+        Ok(String::from_utf8(vfs.read(path).block_on()?)?)
+    }
+
+    #[test]
+    fn first_mount_wins() {
+        let dirs: Vec<_> = (0..3).map(|_| tempfile::tempdir().unwrap()).collect();
+        for (i, dir) in dirs.iter().enumerate() {
+            std::fs::write(dir.path().join("shared.txt"), i.to_string()).unwrap();
+        }
+        std::fs::write(dirs[1].path().join("only.txt"), "1").unwrap();
+
+        let user = tempfile::tempdir().unwrap();
+        let mut vfs = Vfs::new(user.path());
+        vfs.mount(Mount::Dir(dirs[0].path().into()))
+            .mount(Mount::Dir(dirs[1].path().into()));
+        assert_eq!(read(&vfs, "shared.txt").unwrap(), "0");
+        assert_eq!(read(&vfs, "only.txt").unwrap(), "1");
+
+        vfs.mount_overlay(Mount::Dir(dirs[2].path().into()));
+        assert_eq!(read(&vfs, "shared.txt").unwrap(), "2");
+        assert_eq!(
+            vfs.real_path("shared.txt"),
+            Some(dirs[2].path().join("shared.txt"))
+        );
+        assert!(read(&vfs, "missing.txt").is_err());
+        assert_eq!(vfs.real_path("missing.txt"), None);
+    }
+
+    #[test]
+    fn user_paths_go_to_the_user_dir() {
+        let assets = tempfile::tempdir().unwrap();
+        std::fs::write(assets.path().join("config.json"), "asset").unwrap();
+        let user = tempfile::tempdir().unwrap();
+        let mut vfs = Vfs::new(user.path());
+        vfs.mount(Mount::Dir(assets.path().into()));
+
+        vfs.write("user:nested/config.json", b"user")
+            .block_on()
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(user.path().join("nested/config.json")).unwrap(),
+            "user"
+        );
+        assert_eq!(read(&vfs, "user:nested/config.json").unwrap(), "user");
+        // User data never falls back to the mounts.
+        assert!(read(&vfs, "user:config.json").is_err());
+        assert_eq!(read(&vfs, "config.json").unwrap(), "asset");
+    }
+
+    #[test]
+    fn assets_are_read_only() {
+        let assets = tempfile::tempdir().unwrap();
+        std::fs::write(assets.path().join("shader.wgsl"), "old").unwrap();
+        let user = tempfile::tempdir().unwrap();
+        let mut vfs = Vfs::new(user.path());
+        vfs.mount(Mount::Dir(assets.path().into()));
+
+        let error = vfs.write("shader.wgsl", b"new").block_on().unwrap_err();
+        assert!(error.to_string().contains("read-only"));
+        assert!(vfs.write("new.wgsl", b"new").block_on().is_err());
+        assert_eq!(read(&vfs, "shader.wgsl").unwrap(), "old");
+        assert!(!assets.path().join("new.wgsl").exists());
+    }
+
+    #[test]
+    fn relative_paths_stay_inside() {
+        let root = tempfile::tempdir().unwrap();
+        let assets = root.path().join("assets");
+        let user = root.path().join("user");
+        std::fs::create_dir_all(&assets).unwrap();
+        std::fs::write(root.path().join("secret.txt"), "secret").unwrap();
+        let mut vfs = Vfs::new(&user);
+        vfs.mount(Mount::Dir(assets));
+
+        for path in [
+            "../secret.txt",
+            "user:../secret.txt",
+            "./secret.txt",
+            "a/../../secret.txt",
+        ] {
+            assert!(read(&vfs, path).is_err(), "{path}");
+            assert_eq!(vfs.real_path(path), None, "{path}");
+        }
+        assert!(vfs
+            .write("user:../escaped.txt", b"data")
+            .block_on()
+            .is_err());
+        assert!(!root.path().join("escaped.txt").exists());
+        let absolute = format!("user:{}", root.path().join("escaped.txt").display());
+        assert!(vfs.write(&absolute, b"data").block_on().is_err());
+        assert!(!root.path().join("escaped.txt").exists());
+
+        // Absolute paths are still passed through.
+        let secret = root.path().join("secret.txt");
+        assert_eq!(read(&vfs, secret.to_str().unwrap()).unwrap(), "secret");
+    }
+
+    #[test]
+    fn failed_writes_leave_no_temp_file() {
+        let user = tempfile::tempdir().unwrap();
+        let vfs = Vfs::new(user.path());
+        // Renaming a file over a directory fails.
+        std::fs::create_dir_all(user.path().join("taken/child")).unwrap();
+
+        assert!(vfs.write("user:taken", b"data").block_on().is_err());
+        assert!(!user.path().join("taken.tmp").exists());
+        assert!(user.path().join("taken/child").is_dir());
+    }
+}
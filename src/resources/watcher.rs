@@ -0,0 +1,146 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use notify_debouncer_mini::{
+    new_debouncer,
+    notify::{RecommendedWatcher, RecursiveMode},
+    DebounceEventResult, Debouncer,
+};
+
+use super::vfs;
+
+/// Watches individual asset files and reports them once they have stopped
+/// changing for the debounce period.
+///
+/// The parent directory of each file is watched rather than the file itself
+/// so that editors which save by writing a temp file and renaming it over
+/// the original are still picked up.
+pub struct AssetWatcher {
+    debouncer: Debouncer<RecommendedWatcher>,
+    changes: flume::Receiver<PathBuf>,
+    dirs: Vec<PathBuf>,
+    files: HashMap<PathBuf, String>,
+}
+
+impl AssetWatcher {
+    pub fn new(
+        debounce: Duration,
+        mut on_change: impl FnMut() + Send + 'static,
+    ) -> anyhow::Result<Self> {
+        let (sender, changes) = flume::unbounded();
+        let debouncer = new_debouncer(debounce, move |result: DebounceEventResult| match result {
+            Ok(events) => {
+                for event in events {
+                    let _ = sender.send(event.path);
+                }
+                on_change();
+            }
+            Err(e) => log::warn!("AssetWatcher: {e}"),
+        })?;
+
+        Ok(Self {
+            debouncer,
+            changes,
+            dirs: Vec::new(),
+            files: HashMap::new(),
+        })
+    }
+
+    /// Watches the file that `path` currently resolves to in the [`vfs`].
+    pub fn watch(&mut self, path: &str) -> anyhow::Result<()> {
+        let real_path = vfs::get()
+            .real_path(path)
+            .ok_or_else(|| anyhow::anyhow!("{path:?} not found in any mount"))?;
+        let dir = match real_path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        std::fs::create_dir_all(dir)?;
+        let dir = dir.canonicalize()?;
+        let file_name = real_path
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("{path:?} is not a file"))?;
+
+        if !self.dirs.contains(&dir) {
+            self.debouncer
+                .watcher()
+                .watch(&dir, RecursiveMode::NonRecursive)?;
+            self.dirs.push(dir.clone());
+        }
+
+        self.files.insert(dir.join(file_name), path.to_owned());
+
+        Ok(())
+    }
+
+    /// Returns the watched paths that changed since the last call, in the
+    /// form they were passed to [`AssetWatcher::watch`].
+    pub fn changed(&self) -> Vec<String> {
+        let mut changed = Vec::new();
+        for path in self.changes.try_iter() {
+            if let Some(asset) = self.files.get(&path) {
+                if !changed.contains(asset) {
+                    changed.push(asset.clone());
+                }
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A watcher whose change events come from the returned sender instead
+    /// of the file system.
+    fn watcher() -> (AssetWatcher, flume::Sender<PathBuf>) {
+        let mut watcher = AssetWatcher::new(Duration::from_secs(60), || ()).unwrap();
+        let (sender, changes) = flume::unbounded();
+        watcher.changes = changes;
+        (watcher, sender)
+    }
+
+    #[test]
+    fn maps_real_paths_to_watched_paths() {
+        let (mut watcher, sender) = watcher();
+        let dir = tempfile::tempdir().unwrap();
+        let absolute = dir.path().join("scene.json");
+        std::fs::write(&absolute, "{}").unwrap();
+        let absolute = absolute.to_str().unwrap();
+
+        watcher.watch("Cargo.toml").unwrap();
+        watcher.watch("user:watched.json").unwrap();
+        watcher.watch(absolute).unwrap();
+
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .canonicalize()
+            .unwrap();
+        let user_dir = vfs::get().user_dir().canonicalize().unwrap();
+        let dir = dir.path().canonicalize().unwrap();
+        for path in [
+            user_dir.join("watched.json"),
+            manifest_dir.join("Cargo.toml"),
+            manifest_dir.join("Cargo.lock"),
+            dir.join("scene.json"),
+            user_dir.join("watched.json"),
+        ] {
+            sender.send(path).unwrap();
+        }
+
+        assert_eq!(
+            watcher.changed(),
+            ["user:watched.json", "Cargo.toml", absolute]
+        );
+        assert!(watcher.changed().is_empty());
+    }
+
+    #[test]
+    fn rejects_missing_assets() {
+        let (mut watcher, _) = watcher();
+        assert!(watcher.watch("no/such/asset.json").is_err());
+    }
+}
@@ -1,57 +1,376 @@
-use std::ops::Deref;
+use std::{cell::Cell, ops::Deref};
 
 use winit::{
     event_loop::EventLoop,
-    window::{Fullscreen, Window as WinitWindow, WindowBuilder}, dpi::PhysicalSize,
+    monitor::{MonitorHandle, VideoMode},
+    window::{CursorGrabMode, CursorIcon, Fullscreen, Window as WinitWindow, WindowBuilder}, dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Size},
 };
 
-use crate::Config;
+use crate::{config, Config};
 
 pub struct Window {
     window: WinitWindow,
+    exclusive_fullscreen: bool,
+    video_mode: Option<config::VideoMode>,
+    cursor_mode: Cell<CursorMode>,
+    focused: Cell<bool>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum CursorMode {
+    #[default]
+    Free,
+    /// Visible, but can't leave the window.
+    Confined,
+    /// Hidden and held in place, for driving a camera with mouse deltas.
+    Locked,
+    /// Hidden while over the window, but free to move.
+    Hidden,
+}
+
+/// Window operations requested from outside of `lib::run`, see
+/// [`Window::apply`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    SetTitle(String),
+    SetCursorVisible(bool),
+    SetCursorIcon(CursorIcon),
+    SetCursorMode(CursorMode),
+    SetFullscreen(bool),
+    ToggleFullscreen,
+    /// Moves the window, or the fullscreen output, to the next monitor.
+    NextMonitor,
+    /// Moves the window, or the fullscreen output, to the named monitor if
+    /// it is connected.
+    SetMonitor(String),
+    /// Resizes the window unless it is maximized or fullscreen.
+    SetSize(Size),
+}
+
+impl Command {
+    /// The commands that bring the window from the `old` config's settings
+    /// to the `new` one's, for reloading the config file.
+    pub fn for_reload(old: &Config, new: &Config) -> Vec<Command> {
+        let size = |config: &Config| -> Size {
+            match config.physical_size {
+                Some((width, height)) => PhysicalSize::new(width, height).into(),
+                None => LogicalSize::new(config.width, config.height).into(),
+            }
+        };
+
+        let mut commands = Vec::new();
+        if new.monitor != old.monitor {
+            if let Some(monitor) = &new.monitor {
+                commands.push(Command::SetMonitor(monitor.clone()));
+            }
+        }
+        if size(new) != size(old) {
+            commands.push(Command::SetSize(size(new)));
+        }
+        if new.fullscreen != old.fullscreen {
+            commands.push(Command::SetFullscreen(new.fullscreen));
+        }
+        commands
+    }
+}
+
+/// The video modes a monitor supports, see [`Window::video_modes`].
+#[derive(Debug, Clone)]
+pub struct MonitorModes {
+    pub monitor: Option<String>,
+    pub modes: Vec<config::VideoMode>,
 }
 
 impl Window {
     pub fn new(config: &Config, event_loop: &EventLoop<()>) -> anyhow::Result<Window> {
-        let window = WindowBuilder::new()
+        let monitors: Vec<_> = event_loop.available_monitors().collect();
+        let monitor = monitors
+            .iter()
+            .find(|m| config.monitor.is_some() && m.name() == config.monitor)
+            .cloned()
+            .or_else(|| event_loop.primary_monitor())
+            .or_else(|| monitors.first().cloned());
+
+        // Config sizes are logical, don't let them outgrow the monitor on
+        // a display with a higher scale factor.
+        let mut size = match config.physical_size {
+            Some((width, height)) => {
+                let scale_factor = monitor.as_ref().map_or(1.0, |m| m.scale_factor());
+                PhysicalSize::new(width, height).to_logical(scale_factor)
+            }
+            None => LogicalSize::new(config.width, config.height),
+        };
+        if let Some(monitor) = &monitor {
+            let max: LogicalSize<u32> = monitor.size().to_logical(monitor.scale_factor());
+            size.width = size.width.min(max.width);
+            size.height = size.height.min(max.height);
+        }
+
+        let mut builder = WindowBuilder::new()
             .with_visible(false)
-            .build(&event_loop)?;
+            .with_inner_size(size)
+            .with_maximized(config.maximized);
+
+        if let Some((x, y)) = config.position {
+            let scale_factor = monitor.as_ref().map_or(1.0, |m| m.scale_factor());
+            builder = builder.with_position(restore_position(
+                PhysicalPosition { x, y },
+                size.to_physical(scale_factor),
+                config.monitor.as_deref(),
+                &monitors.iter().map(MonitorRect::from).collect::<Vec<_>>(),
+            ));
+        }
+
+        let window = Self {
+            window: builder.build(event_loop)?,
+            exclusive_fullscreen: config.exclusive_fullscreen,
+            video_mode: config.video_mode,
+            cursor_mode: Cell::new(CursorMode::Free),
+            focused: Cell::new(true),
+        };
 
         if config.fullscreen {
-            window.set_fullscreen(Some(Fullscreen::Borderless(find_or_first(
-                window.available_monitors(),
-                |m| m.name() == config.monitor,
-            ))))
-        } else {
-            let _ = window.request_inner_size(PhysicalSize {
-                width: config.width,
-                height: config.height,
+            let monitor = find_or_first(window.available_monitors(), |m| {
+                m.name() == config.monitor
             });
+            window.set_fullscreen(Some(window.fullscreen_on(monitor)));
         }
 
-        Ok(Self { window })
+        Ok(window)
     }
 
     pub fn show(&self) {
         self.window.set_visible(true);
     }
 
-    pub fn toggle_fullscreen(&mut self) {
+    /// Returns the mode that took effect for [`Command::SetCursorMode`].
+    pub fn apply(&self, command: Command) -> Option<CursorMode> {
+        match command {
+            Command::SetTitle(title) => self.window.set_title(&title),
+            Command::SetCursorVisible(visible) => self.window.set_cursor_visible(visible),
+            Command::SetCursorIcon(icon) => self.window.set_cursor_icon(icon),
+            Command::SetCursorMode(mode) => return Some(self.set_cursor_mode(mode)),
+            Command::SetFullscreen(fullscreen) => {
+                if fullscreen != self.is_fullscreen() {
+                    self.toggle_fullscreen();
+                }
+            }
+            Command::ToggleFullscreen => self.toggle_fullscreen(),
+            Command::NextMonitor => self.next_monitor(),
+            Command::SetMonitor(name) => {
+                let monitor = self
+                    .window
+                    .available_monitors()
+                    .find(|m| m.name().as_deref() == Some(&name));
+                match monitor {
+                    Some(monitor) => self.move_to(self.window.current_monitor(), monitor),
+                    None => log::warn!("Monitor {name:?} is not connected"),
+                }
+            }
+            Command::SetSize(size) => {
+                if self.is_windowed() {
+                    let _ = self.window.request_inner_size(size);
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the mode that took effect. Platforms that can't lock the
+    /// cursor get a confined one instead and vice versa.
+    pub fn set_cursor_mode(&self, mode: CursorMode) -> CursorMode {
+        self.cursor_mode.set(mode);
+        if self.focused.get() {
+            self.apply_cursor_mode(mode)
+        } else {
+            mode
+        }
+    }
+
+    pub fn cursor_mode(&self) -> CursorMode {
+        self.cursor_mode.get()
+    }
+
+    /// Releases the cursor while the window isn't focused and restores the
+    /// requested [`CursorMode`] when it is again.
+    pub fn set_focused(&self, focused: bool) {
+        self.focused.set(focused);
+        if focused {
+            self.apply_cursor_mode(self.cursor_mode.get());
+        } else {
+            let _ = self.window.set_cursor_grab(CursorGrabMode::None);
+            self.window.set_cursor_visible(true);
+        }
+    }
+
+    fn apply_cursor_mode(&self, mode: CursorMode) -> CursorMode {
+        let (grab, visible) = match mode {
+            CursorMode::Free => (CursorGrabMode::None, true),
+            CursorMode::Confined => (CursorGrabMode::Confined, true),
+            CursorMode::Locked => (CursorGrabMode::Locked, false),
+            CursorMode::Hidden => (CursorGrabMode::None, false),
+        };
+
+        let fallback = match mode {
+            CursorMode::Locked => Some((CursorGrabMode::Confined, CursorMode::Confined)),
+            CursorMode::Confined => Some((CursorGrabMode::Locked, CursorMode::Locked)),
+            CursorMode::Free | CursorMode::Hidden => None,
+        };
+        let ungrabbed = if visible {
+            CursorMode::Free
+        } else {
+            CursorMode::Hidden
+        };
+
+        let mode = match (self.window.set_cursor_grab(grab), fallback) {
+            (Ok(()), _) => mode,
+            (Err(e), Some((grab, fallback))) => match self.window.set_cursor_grab(grab) {
+                Ok(()) => {
+                    log::info!("{mode:?} cursor unsupported ({e}), using {fallback:?}");
+                    fallback
+                }
+                Err(e) => {
+                    log::warn!("Unable to grab the cursor: {e}");
+                    ungrabbed
+                }
+            },
+            (Err(e), None) => {
+                log::warn!("Unable to release the cursor: {e}");
+                ungrabbed
+            }
+        };
+
+        self.window.set_cursor_visible(visible);
+
+        mode
+    }
+
+    pub fn toggle_fullscreen(&self) {
         if self.is_fullscreen() {
             self.window.set_fullscreen(None);
         } else {
-            self.window
-                .set_fullscreen(Some(Fullscreen::Borderless(None)));
+            let monitor = self.window.current_monitor();
+            self.window.set_fullscreen(Some(self.fullscreen_on(monitor)));
         }
     }
 
+    pub fn next_monitor(&self) {
+        let monitors: Vec<_> = self.window.available_monitors().collect();
+        let current = self.window.current_monitor();
+        let index = current
+            .as_ref()
+            .and_then(|current| monitors.iter().position(|m| m == current))
+            .unwrap_or(0);
+        let Some(next) = monitors.get((index + 1) % monitors.len().max(1)).cloned() else {
+            return;
+        };
+        self.move_to(current, next);
+    }
+
+    fn move_to(&self, current: Option<MonitorHandle>, next: MonitorHandle) {
+        if self.is_fullscreen() {
+            self.window.set_fullscreen(Some(self.fullscreen_on(Some(next))));
+            return;
+        }
+
+        // Keep the same offset from the top left corner of the monitor.
+        let Ok(position) = self.window.outer_position() else {
+            return;
+        };
+        let origin = current.map(|m| m.position()).unwrap_or_default();
+        let position = PhysicalPosition {
+            x: next.position().x + position.x - origin.x,
+            y: next.position().y + position.y - origin.y,
+        };
+        self.window.set_outer_position(clamp_onto(
+            position,
+            self.window.outer_size(),
+            &MonitorRect::from(&next),
+        ));
+    }
+
+    /// Exclusive fullscreen if it's configured and the monitor supports the
+    /// video mode, borderless otherwise.
+    fn fullscreen_on(&self, monitor: Option<MonitorHandle>) -> Fullscreen {
+        if self.exclusive_fullscreen {
+            let mode = monitor
+                .as_ref()
+                .and_then(|m| select_video_mode(m, self.video_mode));
+            if let Some(mode) = mode {
+                return Fullscreen::Exclusive(mode);
+            }
+            log::warn!(
+                "Video mode {:?} unavailable, falling back to borderless fullscreen",
+                self.video_mode
+            );
+        }
+        Fullscreen::Borderless(monitor)
+    }
+
+    pub fn video_modes(&self) -> Vec<MonitorModes> {
+        self.window
+            .available_monitors()
+            .map(|monitor| MonitorModes {
+                monitor: monitor.name(),
+                modes: monitor
+                    .video_modes()
+                    .map(|mode| config::VideoMode {
+                        width: mode.size().width,
+                        height: mode.size().height,
+                        refresh_rate_millihertz: Some(mode.refresh_rate_millihertz()),
+                        bit_depth: Some(mode.bit_depth()),
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
     pub fn is_fullscreen(&self) -> bool {
         self.window.fullscreen().is_some()
     }
 
+    /// The physical inner size that `config`'s logical size maps to at
+    /// `scale_factor`.
+    pub fn configured_size(config: &Config, scale_factor: f64) -> PhysicalSize<u32> {
+        LogicalSize::new(config.width, config.height).to_physical(scale_factor)
+    }
+
+    pub fn is_windowed(&self) -> bool {
+        !self.is_fullscreen() && !self.window.is_maximized()
+    }
+
+    /// Records the window state in `config`. The size and position are only
+    /// updated while the window is in its normal windowed state, so they
+    /// survive a session that ends maximized or fullscreen.
     pub fn modify_config(&self, config: &mut Config) {
+        if let Some((width, height)) = config.physical_size.take() {
+            let size: LogicalSize<u32> =
+                PhysicalSize::new(width, height).to_logical(self.scale_factor());
+            config.width = size.width;
+            config.height = size.height;
+        }
+
         config.fullscreen = self.is_fullscreen();
-        config.monitor = self.window.current_monitor().map(|m| m.name()).flatten();
+        config.monitor = self.window.current_monitor().and_then(|m| m.name());
+
+        if config.fullscreen || self.window.is_minimized() == Some(true) {
+            return;
+        }
+
+        config.maximized = self.window.is_maximized();
+        if config.maximized {
+            return;
+        }
+
+        let size: LogicalSize<u32> = self.window.inner_size().to_logical(self.scale_factor());
+        if size.width > 0 && size.height > 0 {
+            config.width = size.width;
+            config.height = size.height;
+        }
+
+        if let Ok(position) = self.window.outer_position() {
+            config.position = Some((position.x, position.y));
+        }
     }
 }
 
@@ -83,3 +402,329 @@ fn find_or_first<T>(
 
     found
 }
+
+/// Finds a mode with the requested resolution (the monitor's native one if
+/// `request` is `None`) and bit depth, see [`pick_video_mode`].
+fn select_video_mode(
+    monitor: &MonitorHandle,
+    request: Option<config::VideoMode>,
+) -> Option<VideoMode> {
+    let modes: Vec<_> = monitor.video_modes().collect();
+    let candidates: Vec<_> = modes
+        .iter()
+        .map(|m| (m.size(), m.bit_depth(), m.refresh_rate_millihertz()))
+        .collect();
+    let index = pick_video_mode(monitor.size(), request, &candidates)?;
+    Some(modes[index].clone())
+}
+
+/// The index of the mode in `modes`, given as size, bit depth and refresh
+/// rate in millihertz, with the requested resolution (`native` if `request`
+/// is `None`) and bit depth. Prefers the closest refresh rate if one was
+/// requested and the highest otherwise. `None` if no mode has the
+/// resolution and bit depth.
+fn pick_video_mode(
+    native: PhysicalSize<u32>,
+    request: Option<config::VideoMode>,
+    modes: &[(PhysicalSize<u32>, u16, u32)],
+) -> Option<usize> {
+    let size = match request {
+        Some(request) => PhysicalSize::new(request.width, request.height),
+        None => native,
+    };
+    let bit_depth = request.and_then(|r| r.bit_depth);
+    let refresh_rate = request.and_then(|r| r.refresh_rate_millihertz);
+
+    let candidates = modes
+        .iter()
+        .enumerate()
+        .filter(|(_, (m_size, _, _))| *m_size == size)
+        .filter(|(_, (_, m_depth, _))| bit_depth.is_none_or(|depth| *m_depth == depth));
+
+    let (index, _) = match refresh_rate {
+        Some(rate) => candidates.min_by_key(|(_, (_, m_depth, m_rate))| {
+            (m_rate.abs_diff(rate), u16::MAX - m_depth)
+        }),
+        None => candidates.max_by_key(|(_, (_, m_depth, m_rate))| (*m_rate, *m_depth)),
+    }?;
+    Some(index)
+}
+
+/// Where a monitor is, in physical pixels. Window placement only needs
+/// this much, which keeps it testable without a display.
+#[derive(Debug, Clone, PartialEq)]
+struct MonitorRect {
+    name: Option<String>,
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+}
+
+impl From<&MonitorHandle> for MonitorRect {
+    fn from(monitor: &MonitorHandle) -> Self {
+        Self {
+            name: monitor.name(),
+            position: monitor.position(),
+            size: monitor.size(),
+        }
+    }
+}
+
+/// Keeps `position` if the window still overlaps the monitor it was saved
+/// on. Otherwise the window is clamped onto the connected monitor it
+/// overlaps the most, or the first one if it is entirely off screen.
+fn restore_position(
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+    monitor: Option<&str>,
+    monitors: &[MonitorRect],
+) -> PhysicalPosition<i32> {
+    let overlap = |m: &MonitorRect| {
+        let (m_pos, m_size) = (m.position, m.size);
+        let w = (position.x + size.width as i32).min(m_pos.x + m_size.width as i32)
+            - position.x.max(m_pos.x);
+        let h = (position.y + size.height as i32).min(m_pos.y + m_size.height as i32)
+            - position.y.max(m_pos.y);
+        w.max(0) as i64 * h.max(0) as i64
+    };
+
+    let saved = monitors
+        .iter()
+        .find(|m| monitor.is_some() && m.name.as_deref() == monitor);
+    if saved.is_some_and(|m| overlap(m) > 0) {
+        return position;
+    }
+
+    let target = monitors
+        .iter()
+        .filter(|m| overlap(m) > 0)
+        .max_by_key(|m| overlap(m))
+        .or(monitors.first());
+    match target {
+        Some(target) => clamp_onto(position, size, target),
+        None => position,
+    }
+}
+
+/// Moves `position` so a window of `size` fits onto `monitor` where possible.
+/// A window larger than the monitor goes into its top left corner.
+fn clamp_onto(
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+    monitor: &MonitorRect,
+) -> PhysicalPosition<i32> {
+    let (m_pos, m_size) = (monitor.position, monitor.size);
+    PhysicalPosition {
+        x: position
+            .x
+            .clamp(m_pos.x, m_pos.x + (m_size.width as i32 - size.width as i32).max(0)),
+        y: position
+            .y
+            .clamp(m_pos.y, m_pos.y + (m_size.height as i32 - size.height as i32).max(0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(name: &str, x: i32, y: i32, width: u32, height: u32) -> MonitorRect {
+        MonitorRect {
+            name: Some(name.to_owned()),
+            position: PhysicalPosition::new(x, y),
+            size: PhysicalSize::new(width, height),
+        }
+    }
+
+    #[test]
+    fn restores_positions_onto_connected_monitors() {
+        let monitors = [
+            monitor("left", 0, 0, 1920, 1080),
+            monitor("right", 1920, 0, 1920, 1080),
+        ];
+        let size = PhysicalSize::new(800, 600);
+
+        // Still on the saved monitor.
+        let position = PhysicalPosition::new(2000, 100);
+        assert_eq!(
+            restore_position(position, size, Some("right"), &monitors),
+            position
+        );
+
+        // The saved monitor was at x = 3840 and is disconnected now, so the
+        // window lands on the right edge of the first monitor.
+        assert_eq!(
+            restore_position(
+                PhysicalPosition::new(4000, 100),
+                size,
+                Some("gone"),
+                &monitors
+            ),
+            PhysicalPosition::new(1120, 100)
+        );
+    }
+
+    #[test]
+    fn clamps_partly_off_screen_windows() {
+        let monitors = [
+            monitor("left", 0, 0, 1920, 1080),
+            monitor("right", 1920, 0, 1920, 1080),
+        ];
+        let size = PhysicalSize::new(800, 600);
+
+        // Mostly on the right monitor, hanging off its bottom right corner.
+        assert_eq!(
+            restore_position(
+                PhysicalPosition::new(3500, 900),
+                size,
+                Some("gone"),
+                &monitors
+            ),
+            PhysicalPosition::new(3040, 480)
+        );
+        // Hanging off the top left, clamped onto the only monitor it touches.
+        assert_eq!(
+            restore_position(PhysicalPosition::new(-200, -100), size, None, &monitors),
+            PhysicalPosition::new(0, 0)
+        );
+    }
+
+    #[test]
+    fn puts_oversized_windows_into_the_corner() {
+        let small = monitor("small", 1920, 0, 1280, 720);
+        assert_eq!(
+            clamp_onto(
+                PhysicalPosition::new(2000, 200),
+                PhysicalSize::new(1920, 1080),
+                &small
+            ),
+            PhysicalPosition::new(1920, 0)
+        );
+        assert_eq!(
+            restore_position(
+                PhysicalPosition::new(-5000, -5000),
+                PhysicalSize::new(1920, 1080),
+                Some("small"),
+                &[small]
+            ),
+            PhysicalPosition::new(1920, 0)
+        );
+    }
+
+    fn mode(width: u32, height: u32, bit_depth: u16, hz: u32) -> (PhysicalSize<u32>, u16, u32) {
+        (PhysicalSize::new(width, height), bit_depth, hz * 1000)
+    }
+
+    fn request(bit_depth: Option<u16>, refresh_rate_hz: Option<u32>) -> Option<config::VideoMode> {
+        Some(config::VideoMode {
+            width: 1920,
+            height: 1080,
+            bit_depth,
+            refresh_rate_millihertz: refresh_rate_hz.map(|hz| hz * 1000),
+        })
+    }
+
+    #[test]
+    fn picks_exact_video_modes() {
+        let native = PhysicalSize::new(2560, 1440);
+        let modes = [
+            mode(2560, 1440, 32, 144),
+            mode(1920, 1080, 24, 60),
+            mode(1920, 1080, 32, 60),
+            mode(1920, 1080, 32, 144),
+        ];
+        assert_eq!(
+            pick_video_mode(native, request(Some(32), Some(60)), &modes),
+            Some(2)
+        );
+        assert_eq!(
+            pick_video_mode(native, request(Some(24), None), &modes),
+            Some(1)
+        );
+        // Without a refresh rate, the fastest mode wins.
+        assert_eq!(
+            pick_video_mode(native, request(None, None), &modes),
+            Some(3)
+        );
+        // Without a request, the native resolution is used.
+        assert_eq!(pick_video_mode(native, None, &modes), Some(0));
+    }
+
+    #[test]
+    fn picks_the_nearest_refresh_rate() {
+        let native = PhysicalSize::new(1920, 1080);
+        let modes = [
+            mode(1920, 1080, 24, 60),
+            mode(1920, 1080, 24, 120),
+            mode(1920, 1080, 32, 120),
+            mode(1920, 1080, 32, 165),
+        ];
+        // 120 Hz is closest, and the deeper of the two 120 Hz modes wins.
+        assert_eq!(
+            pick_video_mode(native, request(None, Some(100)), &modes),
+            Some(2)
+        );
+        assert_eq!(
+            pick_video_mode(native, request(Some(24), Some(100)), &modes),
+            Some(1)
+        );
+        assert_eq!(
+            pick_video_mode(native, request(None, Some(240)), &modes),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn picks_nothing_without_a_matching_mode() {
+        let native = PhysicalSize::new(1920, 1080);
+        assert_eq!(pick_video_mode(native, None, &[]), None);
+        assert_eq!(pick_video_mode(native, request(None, Some(60)), &[]), None);
+        let modes = [mode(1920, 1080, 24, 60), mode(1280, 720, 32, 60)];
+        assert_eq!(
+            pick_video_mode(native, request(Some(32), None), &modes),
+            None
+        );
+    }
+
+    #[test]
+    fn reloads_changed_window_settings() {
+        let old = Config::default();
+        assert_eq!(Command::for_reload(&old, &old.clone()), []);
+
+        let new = Config {
+            width: 1280,
+            height: 720,
+            fullscreen: true,
+            monitor: Some("DP-1".to_owned()),
+            ..old.clone()
+        };
+        assert_eq!(
+            Command::for_reload(&old, &new),
+            [
+                Command::SetMonitor("DP-1".to_owned()),
+                Command::SetSize(LogicalSize::new(1280, 720).into()),
+                Command::SetFullscreen(true),
+            ]
+        );
+
+        // Removing the monitor leaves the window where it is.
+        assert_eq!(
+            Command::for_reload(
+                &new,
+                &Config {
+                    monitor: None,
+                    ..new.clone()
+                }
+            ),
+            []
+        );
+
+        let physical = Config {
+            physical_size: Some((2560, 1440)),
+            ..old.clone()
+        };
+        assert_eq!(
+            Command::for_reload(&old, &physical),
+            [Command::SetSize(PhysicalSize::new(2560, 1440).into())]
+        );
+    }
+}